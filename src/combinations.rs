@@ -1,19 +1,25 @@
+use rayon::iter::plumbing::{bridge, Consumer, Producer, ProducerCallback, UnindexedConsumer};
+use rayon::prelude::*;
+use crate::counting;
 
+// combinations are produced in lexicographic order of indices,
+// `front..back` is the range of ranks that haven't been yielded yet
 pub struct CombinationIterator<'a, T, const N: usize> {
     slice: &'a [T],
-    counters: [usize; N]
+    counters: [usize; N],
+    front: usize,
+    back: usize,
 }
 
 impl<'a, T, const N: usize> Iterator for CombinationIterator<'a, T, N> {
     type Item = [&'a T; N];
 
     fn next(&mut self) -> Option<Self::Item> {
-
-        if self.counters.iter().enumerate().any(|(i, x)| {
-            x == &(self.slice.len() - (N - i - 1))
-        }) { return None; }
+        if self.front == self.back { return None; }
+        self.front += 1;
 
         let out = Some(self.counters.map(|i| &self.slice[i]));
+        if self.front == self.back { return out; }
 
         for i in (1..N).rev() {
             self.counters[i] += 1;
@@ -30,34 +36,395 @@ impl<'a, T, const N: usize> Iterator for CombinationIterator<'a, T, N> {
 
         out
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.back - self.front, Some(self.back - self.front))
+    }
+
+    // jumps straight to the combination instead of stepping through the ones before it,
+    // see `combination_unrank` for the cost
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.back - self.front {
+            self.front = self.back;
+            return None;
+        }
+        self.front += n;
+        self.counters = combination_unrank(self.slice.len(), self.front);
+        self.next()
+    }
 }
 
+impl<'a, T, const N: usize> DoubleEndedIterator for CombinationIterator<'a, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back { return None; }
+        self.back -= 1;
+        Some(combination_unrank::<N>(self.slice.len(), self.back).map(|i| &self.slice[i]))
+    }
+}
+
+impl<'a, T, const N: usize> ExactSizeIterator for CombinationIterator<'a, T, N> {}
+
 impl<'a, T, const N: usize> CombinationIterator<'a, T, N> {
+    pub(crate) fn new(slice: &'a [T]) -> Self {
+        Self::with_ranks(slice, 0, binomial(slice.len(), N))
+    }
+
+    fn with_ranks(slice: &'a [T], front: usize, back: usize) -> Self {
+        let counters = if front < back {
+            combination_unrank(slice.len(), front)
+        } else {
+            [0; N]
+        };
+
+        CombinationIterator {
+            slice,
+            counters,
+            front,
+            back,
+        }
+    }
+}
+
+// position of the (sorted) indices in the lexicographic order of all combinations of `n` items.
+// counted from the back with d_i = n - 1 - indices[i], the rank is the combinadic
+// C(d_0, k) + C(d_1, k - 1) + ... + C(d_(k-1), 1)
+pub(crate) fn combination_rank<const N: usize>(n: usize, indices: &[usize; N]) -> usize {
+    let from_back = indices.iter().enumerate()
+        .map(|(i, &x)| binomial(n - 1 - x, N - i))
+        .sum::<usize>();
+    binomial(n, N) - 1 - from_back
+}
+
+// inverse of `combination_rank`. the d_i are strictly decreasing and each one is the largest
+// d with C(d, k - i) still within what's left of the rank, so it's found by binary search.
+// that's O(k log n) binomial coefficients of O(k) multiplications each, whatever the rank
+pub(crate) fn combination_unrank<const N: usize>(n: usize, rank: usize) -> [usize; N] {
+    let mut indices = [0; N];
+    let mut rest = binomial(n, N) - 1 - rank;
+    let mut bound = n;
+    for (i, index) in indices.iter_mut().enumerate() {
+        let k = N - i;
+        // coefficients too big for usize are bigger than any rank
+        let fits = |d| counting::binomial::<usize>(d, k).is_some_and(|c| c <= rest);
+        // C(0, k) = 0 always fits, so `lo` is a valid answer from the start
+        let (mut lo, mut hi) = (0, bound);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if fits(mid) { lo = mid; } else { hi = mid; }
+        }
+        rest -= binomial(lo, k);
+        *index = n - 1 - lo;
+        bound = lo;
+    }
+    indices
+}
+
+impl<'a, T: Sync, const N: usize> IntoParallelIterator for CombinationIterator<'a, T, N> {
+    type Iter = ParCombinationIterator<'a, T, N>;
+    type Item = [&'a T; N];
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParCombinationIterator {
+            slice: self.slice,
+            front: self.front,
+            back: self.back,
+        }
+    }
+}
+
+// splits the remaining ranks evenly between rayon's threads
+pub struct ParCombinationIterator<'a, T, const N: usize> {
+    slice: &'a [T],
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T: Sync, const N: usize> ParallelIterator for ParCombinationIterator<'a, T, N> {
+    type Item = [&'a T; N];
+
+    fn drive_unindexed<C: UnindexedConsumer<Self::Item>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.back - self.front)
+    }
+}
+
+impl<'a, T: Sync, const N: usize> IndexedParallelIterator for ParCombinationIterator<'a, T, N> {
+    fn len(&self) -> usize {
+        self.back - self.front
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        callback.callback(self)
+    }
+}
+
+impl<'a, T: Sync, const N: usize> Producer for ParCombinationIterator<'a, T, N> {
+    type Item = [&'a T; N];
+    type IntoIter = CombinationIterator<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        CombinationIterator::with_ranks(self.slice, self.front, self.back)
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        let mid = self.front + index;
+        (Self { back: mid, ..self }, Self { front: mid, ..self })
+    }
+}
+
+pub struct CartesianIterator<'a, T, const N: usize> {
+    slice: &'a [T],
+    counters: [usize; N],
+    remaining: usize,
+}
+
+impl<'a, T, const N: usize> CartesianIterator<'a, T, N> {
+    pub(crate) fn new(slice: &'a [T]) -> Self {
+        Self {
+            slice,
+            counters: [0; N],
+            remaining: slice.len().checked_pow(N as u32).expect("too many items to count"),
+        }
+    }
+}
+
+impl<'a, T, const N: usize> Iterator for CartesianIterator<'a, T, N> {
+    type Item = [&'a T; N];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 { return None; }
+        self.remaining -= 1;
+
+        let out = Some(self.counters.map(|i| &self.slice[i]));
+
+        // odometer, last counter moves fastest
+        for i in (0..N).rev() {
+            self.counters[i] += 1;
+            if self.counters[i] != self.slice.len() { break; }
+            self.counters[i] = 0;
+        }
+
+        out
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, const N: usize> ExactSizeIterator for CartesianIterator<'a, T, N> {}
+
+// ordered selections of N distinct items, in lexicographic order of indices
+pub struct PermutationIterator<'a, T, const N: usize> {
+    slice: &'a [T],
+    counters: [usize; N],
+    remaining: usize,
+}
+
+impl<'a, T, const N: usize> PermutationIterator<'a, T, N> {
     pub(crate) fn new(slice: &'a [T]) -> Self {
         let mut counters = [0; N];
         counters.iter_mut()
             .enumerate()
             .for_each(|(i, x)| *x = i);
 
-        CombinationIterator {
+        Self {
             slice,
-            counters
+            counters,
+            remaining: falling_factorial(slice.len(), N),
         }
     }
 }
 
-pub struct CartesianIterator<'a, T, const N: usize> {
+impl<'a, T, const N: usize> Iterator for PermutationIterator<'a, T, N> {
+    type Item = [&'a T; N];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 { return None; }
+        self.remaining -= 1;
+
+        let out = Some(self.counters.map(|i| &self.slice[i]));
+        if self.remaining == 0 { return out; }
+
+        let n = self.slice.len();
+        for i in (0..N).rev() {
+            let used = &self.counters[..i];
+            // bump this position to the next index not used by the ones before it
+            let Some(next) = (self.counters[i] + 1..n).find(|x| !used.contains(x)) else { continue; };
+            self.counters[i] = next;
+            // and fill the rest with the smallest unused indices
+            let prefix = self.counters;
+            let mut free = (0..n).filter(|x| !prefix[..=i].contains(x));
+            for j in i + 1..N {
+                self.counters[j] = free.next().unwrap();
+            }
+            break;
+        }
+
+        out
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, const N: usize> ExactSizeIterator for PermutationIterator<'a, T, N> {}
+
+pub struct CombinationWithReplacementIterator<'a, T, const N: usize> {
     slice: &'a [T],
-    counters: [usize; N]
+    counters: [usize; N],
+    remaining: usize,
 }
 
-impl<'a, T, const N: usize> CartesianIterator<'a, T, N> {
+impl<'a, T, const N: usize> CombinationWithReplacementIterator<'a, T, N> {
     pub(crate) fn new(slice: &'a [T]) -> Self {
+        let remaining = if slice.is_empty() {
+            if N == 0 { 1 } else { 0 }
+        } else {
+            binomial(slice.len() + N - 1, N)
+        };
+
         Self {
             slice,
             counters: [0; N],
+            remaining,
+        }
+    }
+}
+
+impl<'a, T, const N: usize> Iterator for CombinationWithReplacementIterator<'a, T, N> {
+    type Item = [&'a T; N];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 { return None; }
+        self.remaining -= 1;
+
+        let out = Some(self.counters.map(|i| &self.slice[i]));
+        if self.remaining == 0 { return out; }
+
+        // counters never decrease from left to right
+        let i = (0..N).rev().find(|&i| self.counters[i] + 1 != self.slice.len()).unwrap();
+        let next = self.counters[i] + 1;
+        self.counters[i..].iter_mut().for_each(|x| *x = next);
+
+        out
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, const N: usize> ExactSizeIterator for CombinationWithReplacementIterator<'a, T, N> {}
+
+// same as CombinationIterator, but with k only known at runtime
+pub struct DynCombinationIterator<'a, T> {
+    slice: &'a [T],
+    counters: Vec<usize>,
+    remaining: usize,
+}
+
+impl<'a, T> DynCombinationIterator<'a, T> {
+    pub(crate) fn new(slice: &'a [T], k: usize) -> Self {
+        Self {
+            slice,
+            counters: (0..k).collect(),
+            remaining: binomial(slice.len(), k),
+        }
+    }
+}
+
+impl<'a, T> Iterator for DynCombinationIterator<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 { return None; }
+        self.remaining -= 1;
+
+        let out = Some(self.counters.iter().map(|&i| &self.slice[i]).collect());
+        if self.remaining == 0 { return out; }
+
+        let (n, k) = (self.slice.len(), self.counters.len());
+        let i = (0..k).rev().find(|&i| self.counters[i] != n - k + i).unwrap();
+        self.counters[i] += 1;
+        for j in i + 1..k {
+            self.counters[j] = self.counters[j - 1] + 1;
         }
+
+        out
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
+impl<'a, T> ExactSizeIterator for DynCombinationIterator<'a, T> {}
+
+fn binomial(n: usize, k: usize) -> usize {
+    counting::binomial(n, k).expect("too many combinations to count")
+}
+
+fn falling_factorial(n: usize, k: usize) -> usize {
+    if k > n { return 0; }
+    (n - k + 1..=n).try_fold(1usize, |p, x| p.checked_mul(x)).expect("too many permutations to count")
+}
+
+#[test]
+fn test_iterator_lengths() {
+    use itertools::Itertools;
+
+    let items = [1, 2, 3, 4, 5, 6];
+    let expected = items.iter().combinations(3).collect_vec();
+    let actual = CombinationIterator::<_, 3>::new(&items).map(Vec::from).collect_vec();
+    assert_eq!(actual, expected);
+    assert_eq!(DynCombinationIterator::new(&items, 3).collect_vec(), expected);
+
+    let expected = items.iter().permutations(3).collect_vec();
+    assert_eq!(PermutationIterator::<_, 3>::new(&items).map(Vec::from).collect_vec(), expected);
+
+    let expected = items.iter().combinations_with_replacement(3).collect_vec();
+    assert_eq!(CombinationWithReplacementIterator::<_, 3>::new(&items).map(Vec::from).collect_vec(), expected);
+
+    let expected = (0..3).map(|_| items.iter()).multi_cartesian_product().collect_vec();
+    assert_eq!(CartesianIterator::<_, 3>::new(&items).map(Vec::from).collect_vec(), expected);
+
+    let mut iter = CombinationIterator::<_, 2>::new(&items);
+    assert_eq!(iter.len(), 15);
+    iter.next();
+    assert_eq!(iter.len(), 14);
+    assert_eq!(CombinationIterator::<_, 4>::new(&items[..2]).count(), 0);
+}
+
+#[test]
+fn test_combination_ranks() {
+    use itertools::Itertools;
 
+    let items = (0..9).collect_vec();
+    for (rank, c) in CombinationIterator::<_, 4>::new(&items).enumerate() {
+        let indices = c.map(|x| *x);
+        assert_eq!(combination_rank(items.len(), &indices), rank);
+        assert_eq!(combination_unrank::<4>(items.len(), rank), indices);
+        assert_eq!(CombinationIterator::<_, 4>::new(&items).nth(rank), Some(c));
+    }
+
+    let expected = CombinationIterator::<_, 4>::new(&items).collect_vec();
+    assert_eq!(CombinationIterator::<_, 4>::new(&items).rev().collect_vec(), expected.iter().rev().cloned().collect_vec());
+    assert_eq!(CombinationIterator::<_, 4>::new(&items).into_par_iter().collect::<Vec<_>>(), expected);
+
+    // far into a long sequence, including the very last combination
+    let many = (0..200).collect_vec();
+    let total = binomial(many.len(), 3);
+    for rank in [0, 12_345, 1_000_000, total - 1] {
+        let expected = many.iter().combinations(3).nth(rank).unwrap();
+        assert_eq!(CombinationIterator::<_, 3>::new(&many).nth(rank).map(Vec::from), Some(expected));
+    }
+}
@@ -0,0 +1,92 @@
+use num::{BigUint, CheckedAdd, CheckedMul, FromPrimitive, Integer};
+
+// Every count is generic over the output type and returns None if it doesn't fit,
+// so `binomial::<usize>` is the checked version and `binomial::<BigUint>` never fails.
+pub trait Count: Integer + Clone + CheckedAdd + CheckedMul + FromPrimitive {}
+
+impl<T: Integer + Clone + CheckedAdd + CheckedMul + FromPrimitive> Count for T {}
+
+pub fn binomial<T: Count>(n: usize, k: usize) -> Option<T> {
+    if k > n { return Some(T::zero()); }
+    let k = k.min(n - k);
+
+    let mut c = T::one();
+    for i in 0..k {
+        // c * (n - i) / (i + 1) is always exact, divide out the gcd first so that
+        // nothing bigger than the next coefficient is ever computed
+        let d = T::from_usize(i + 1)?;
+        let g = c.gcd(&d);
+        let m = T::from_usize(n - i)? / (d / g.clone());
+        c = (c / g).checked_mul(&m)?;
+    }
+    Some(c)
+}
+
+pub fn multinomial<T: Count>(ks: &[usize]) -> Option<T> {
+    let mut total = 0;
+    let mut c = T::one();
+    for &k in ks {
+        total += k;
+        c = c.checked_mul(&binomial(total, k)?)?;
+    }
+    Some(c)
+}
+
+// number of ways to partition n items into k non-empty sets
+pub fn stirling_second<T: Count>(n: usize, k: usize) -> Option<T> {
+    stirling(n, k, |_, j| j)
+}
+
+// (unsigned) number of permutations of n items with k cycles
+pub fn stirling_first<T: Count>(n: usize, k: usize) -> Option<T> {
+    stirling(n, k, |i, _| i - 1)
+}
+
+// shared recurrence s(i, j) = factor(i, j) * s(i - 1, j) + s(i - 1, j - 1).
+// entries that overflow can only feed into ones bigger than the result, so the
+// overflow only shows up in the answer if the answer itself doesn't fit
+fn stirling<T: Count, FN: Fn(usize, usize) -> usize>(n: usize, k: usize, factor: FN) -> Option<T> {
+    if k > n { return Some(T::zero()); }
+
+    let mut row: Vec<Option<T>> = vec![Some(T::zero()); k + 1];
+    row[0] = Some(T::one());
+    for i in 1..=n {
+        for j in (1..=k.min(i)).rev() {
+            let stay = T::from_usize(factor(i, j))
+                .zip(row[j].clone())
+                .and_then(|(f, s)| f.checked_mul(&s));
+            row[j] = stay.zip(row[j - 1].clone()).and_then(|(a, b)| a.checked_add(&b));
+        }
+        row[0] = Some(T::zero());
+    }
+    row[k].clone()
+}
+
+pub fn binomial_big(n: usize, k: usize) -> BigUint {
+    binomial(n, k).unwrap()
+}
+
+pub fn multinomial_big(ks: &[usize]) -> BigUint {
+    multinomial(ks).unwrap()
+}
+
+pub fn stirling_second_big(n: usize, k: usize) -> BigUint {
+    stirling_second(n, k).unwrap()
+}
+
+pub fn stirling_first_big(n: usize, k: usize) -> BigUint {
+    stirling_first(n, k).unwrap()
+}
+
+#[test]
+fn test_counting() {
+    assert_eq!(binomial::<usize>(10, 3), Some(120));
+    assert_eq!(binomial::<u64>(67, 33), Some(14226520737620288370));
+    assert_eq!(binomial::<u64>(68, 34), None);
+    assert_eq!(binomial_big(68, 34).to_string(), "28453041475240576740");
+    assert_eq!(multinomial::<usize>(&[2, 3, 4]), Some(1260));
+    assert_eq!(stirling_second::<usize>(10, 4), Some(34105));
+    assert_eq!(stirling_first::<usize>(10, 4), Some(723680));
+    assert_eq!(stirling_second::<u8>(6, 6), Some(1));
+    assert_eq!(stirling_first_big(0, 0), BigUint::from(1u8));
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use pathfinding::prelude::brent;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Cycle {
+    // iteration at which the state first enters the loop
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    // earliest iteration with the same state as `target`
+    pub fn reduce(&self, target: usize) -> usize {
+        if target < self.start { target } else { self.start + (target - self.start) % self.length }
+    }
+}
+
+pub fn find_cycle<S, FN>(start: S, mut step: FN) -> Cycle
+    where
+        S: Hash + Eq + Clone,
+        FN: FnMut(S) -> S,
+{
+    let mut seen = HashMap::new();
+    let mut state = start;
+    for i in 0.. {
+        if let Some(first) = seen.insert(state.clone(), i) {
+            return Cycle {
+                start: first,
+                length: i - first,
+            };
+        }
+        state = step(state);
+    }
+    unreachable!()
+}
+
+pub fn find_cycle_brent<S, FN>(start: S, step: FN) -> Cycle
+    where
+        S: Eq + Clone,
+        FN: Fn(S) -> S,
+{
+    let (length, _, start) = brent(start, step);
+    Cycle { start, length }
+}
+
+// simulates until the first repeated state, then skips ahead to `target` using the cycle
+pub fn state_at<S, FN>(start: S, mut step: FN, target: usize) -> S
+    where
+        S: Hash + Eq + Clone,
+        FN: FnMut(S) -> S,
+{
+    let mut states = vec![];
+    let mut seen = HashMap::new();
+    let mut state = start;
+    for i in 0..target {
+        if let Some(&first) = seen.get(&state) {
+            let cycle = Cycle {
+                start: first,
+                length: i - first,
+            };
+            return states.swap_remove(cycle.reduce(target));
+        }
+        seen.insert(state.clone(), i);
+        states.push(state.clone());
+        state = step(state);
+    }
+    state
+}
+
+// same as `state_at` but only keeps a couple of states around, at the cost of stepping more
+pub fn state_at_brent<S, FN>(start: S, step: FN, target: usize) -> S
+    where
+        S: Eq + Clone,
+        FN: Fn(S) -> S,
+{
+    let (length, mut state, start_index) = brent(start.clone(), &step);
+    let remaining = if target < start_index {
+        state = start;
+        target
+    } else {
+        (target - start_index) % length
+    };
+    for _ in 0..remaining {
+        state = step(state);
+    }
+    state
+}
+
+#[test]
+fn test_state_at() {
+    let step = |x: u64| (x * x + 7) % 1009;
+    let brute = |n: usize| (0..n).fold(3, |x, _| step(x));
+    for target in [0, 1, 5, 30, 1000, 1_000_000_000] {
+        let expected = if target > 1000 {
+            let cycle = find_cycle(3, step);
+            brute(cycle.reduce(target))
+        } else {
+            brute(target)
+        };
+        assert_eq!(state_at(3, step, target), expected);
+        assert_eq!(state_at_brent(3, step, target), expected);
+    }
+    assert_eq!(find_cycle(3, step), find_cycle_brent(3, step));
+}
@@ -1,4 +1,6 @@
 ﻿use std::fs::{self};
+use std::path::Path;
+use crate::search;
 
 pub trait Day<TData> {
     fn parse_file(&self, file_content: String) -> TData;
@@ -6,6 +8,11 @@ pub trait Day<TData> {
     fn part_1(&self, data: &TData) -> i64;
 
     fn part_2(&self, data: &TData) -> i64;
+
+    // graphviz views of the puzzle, written when AOC_DOT names a directory
+    fn write_dot(&self, _data: &TData, _dir: &Path) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
 pub fn run_with_test<TData, TDay: Day<TData>>(
@@ -58,22 +65,30 @@ pub fn run_with_test_2<TData, TDay: Day<TData>>(
 }
 
 fn test_part_1<TData, TDay: Day<TData>>(day: &TDay, data: &TData) -> i64 {
+    search::take_stats();
     let now = std::time::Instant::now();
     let part_1 = day.part_1(data);
     let elapsed = now.elapsed();
     println!("Part 1: {}", part_1);
     println!("Elapsed Time: {:.2?}", elapsed);
+    if let Some(stats) = search::take_stats() {
+        println!("{}", stats);
+    }
     println!();
 
     part_1
 }
 
 fn test_part_2<TData, TDay: Day<TData>>(day: &TDay, data: &TData) -> i64 {
+    search::take_stats();
     let now = std::time::Instant::now();
     let part_2 = day.part_2(data);
     let elapsed = now.elapsed();
     println!("Part 2: {}", part_2);
     println!("Elapsed Time: {:.2?}", elapsed);
+    if let Some(stats) = search::take_stats() {
+        println!("{}", stats);
+    }
     println!();
 
     part_2
@@ -110,5 +125,9 @@ pub fn run<TData, TDay: Day<TData>>(day: &TDay, file_name: &str) -> std::io::Res
     let part_1 = test_part_1(day, &file_data);
     let part_2 = test_part_2(day, &file_data);
 
+    if let Some(dir) = std::env::var_os("AOC_DOT") {
+        day.write_dot(&file_data, Path::new(&dir))?;
+    }
+
     Ok((part_1, part_2))
 }
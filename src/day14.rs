@@ -1,6 +1,9 @@
-﻿use std::hash::Hash;
-use pathfinding::prelude::brent;
+﻿use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use colored::Color;
+use crate::cycle::state_at;
 use crate::day::Day;
+use crate::grid::Grid;
 
 pub struct Day14;
 
@@ -10,13 +13,23 @@ pub struct Data {
     h: usize,
 }
 
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq, Hash)]
 pub enum Tile {
     Empty,
     Round,
     Cube,
 }
 
+impl Display for Tile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Tile::Empty => '.',
+            Tile::Round => 'O',
+            Tile::Cube => '#'
+        })
+    }
+}
+
 impl Tile {
     pub fn filled(&self) -> bool {
         match self {
@@ -50,31 +63,17 @@ impl Day<Data> for Day14 {
     }
 
     fn part_2(&self, data: &Data) -> i64 {
-        // get cycle length, cycle start node, and cycle start index using brent's
-        let (l, mut grid, s) =
-            brent(data.grid.clone(), |round| cycle(round, &data));
         const CYCLES: usize = 1_000_000_000;
-        let i = (CYCLES - s) % l;
-        // advance grid 'i' cycles
-        for _ in 0..i {
-            grid = cycle(grid, data);
-        }
+        let grid = state_at(data.grid.clone(), |round| cycle(round, data), CYCLES);
         grid_score(&grid) as i64
     }
 }
 
-fn print_grid(grid: &Vec<Vec<Tile>>) {
-    for y in grid.iter() {
-        for x in y.iter() {
-            print!("{}", match x {
-                Tile::Empty => '.',
-                Tile::Round => 'O',
-                Tile::Cube => '#'
-            });
-        }
-        println!();
-    }
-    println!();
+// highlights the rocks that moved since `previous`
+fn print_grid(grid: &[Vec<Tile>], previous: &[Vec<Tile>]) {
+    let grid = Grid::new(grid.to_vec());
+    let previous = Grid::new(previous.to_vec());
+    println!("{}", grid.highlighted().cells(grid.diff(&previous), Color::Yellow));
 }
 
 fn grid_score(grid: &Vec<Vec<Tile>>) -> usize {
@@ -2,6 +2,7 @@
 use std::fmt::Formatter;
 use crate::day::Day;
 use crate::grid::Grid;
+use crate::vector::{Dir4, Vector};
 
 pub struct Day16;
 
@@ -38,47 +39,21 @@ impl Tile {
         match self {
             Tile::Empty => vec![pos],
             Tile::Mirror(a) => {
-                match a {
-                    &true => {  // /
-                        pos.dir = (-pos.dir.1, -pos.dir.0);
-                        vec![pos]
-                    },
-                    &false => { // \
-                        pos.dir = (pos.dir.1, pos.dir.0);
-                        vec![pos]
-                    }
-                }
+                pos.dir = if *a { pos.dir.reflect_slash() } else { pos.dir.reflect_backslash() };
+                vec![pos]
             }
             Tile::Splitter(a) => {
-                match a {
-                    &true => {  // |
-                        if pos.dir.1 == 0 {
-                            vec![Pos {
-                                dir: (0, 1),
-                                ..pos
-                            },Pos {
-                                dir: (0, -1),
-                                ..pos
-                            }
-                            ]
-                        } else {
-                            vec![pos]
-                        }
-                    },
-                    &false => { // -
-                        if pos.dir.0 == 0 {
-                            vec![Pos {
-                                dir: (1, 0),
-                                ..pos
-                            },Pos {
-                                dir: (-1, 0),
-                                ..pos
-                            }
-                            ]
-                        } else {
-                            vec![pos]
-                        }
-                    }
+                // | splits horizontal beams, - splits vertical ones
+                if *a == pos.dir.is_horizontal() {
+                    vec![Pos {
+                        dir: pos.dir.rotate_cw(),
+                        ..pos
+                    }, Pos {
+                        dir: pos.dir.rotate_ccw(),
+                        ..pos
+                    }]
+                } else {
+                    vec![pos]
                 }
             }
         }
@@ -92,13 +67,12 @@ pub struct Data {
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct Pos {
     pos: (i64, i64),
-    dir: (i64, i64)
+    dir: Dir4
 }
 
 impl Pos {
     pub fn step(&mut self) {
-        self.pos.0 += self.dir.0;
-        self.pos.1 += self.dir.1;
+        self.pos = (Vector::from(self.pos) + self.dir.unit()).into();
     }
     
     pub fn in_bounds(&self, grid: &Grid<Tile>) -> bool {
@@ -119,7 +93,7 @@ impl Day<Data> for Day16 {
     fn part_1(&self, data: &Data) -> i64 {
         solve(Pos {
             pos: (0, 0),
-            dir: (1, 0),
+            dir: Dir4::East,
         }, &data.grid)
         // // for y in 0..(data.grid.h as i64) {
         // //     for x in 0..(data.grid.w as i64) {
@@ -139,24 +113,24 @@ impl Day<Data> for Day16 {
             max = max.max(solve(
                 Pos {
                     pos: (0, y as i64),
-                    dir: (1, 0),
+                    dir: Dir4::East,
                 }, &data.grid));
             max = max.max(solve(
                 Pos {
                     pos: (data.grid.w as i64 - 1, y as i64),
-                    dir: (-1, 0),
+                    dir: Dir4::West,
                 }, &data.grid));
         }
         for x in 0..data.grid.w {
             max = max.max(solve(
                 Pos {
                     pos: (x as i64, 0),
-                    dir: (0, 1),
+                    dir: Dir4::South,
                 }, &data.grid));
             max = max.max(solve(
                 Pos {
                     pos: (x as i64, data.grid.h as i64 - 1),
-                    dir: (0, -1),
+                    dir: Dir4::North,
                 }, &data.grid));
         }
         
@@ -1,15 +1,15 @@
 ﻿use itertools::Itertools;
 use num::Num;
-use crate::day10::Direction;
 use crate::day::Day;
+use crate::vector::{Dir4, Vector};
 
 pub struct Day18;
 
 #[derive(Debug)]
 pub struct Trench {
-    dir: Direction,
+    dir: Dir4,
     dist: i64,
-    dir_hex: Direction,
+    dir_hex: Dir4,
     dist_hex: i64,
 }
 
@@ -19,13 +19,7 @@ impl Day<Data> for Day18 {
     fn parse_file(&self, file_content: String) -> Data {
         file_content.lines().map(|s| {
             let mut s = s.split_whitespace();
-            let dir = match s.next().unwrap() {
-                "R" => Direction::East,
-                "D" => Direction::South,
-                "L" => Direction::West,
-                "U" => Direction::North,
-                x => panic!("{x}")
-            };
+            let dir = s.next().unwrap().parse().unwrap();
 
             let dist = s.next().unwrap().parse().unwrap();
 
@@ -34,10 +28,10 @@ impl Day<Data> for Day18 {
             let dist_hex = &color[..color.len() - 1];
             let dist_hex = i64::from_str_radix(dist_hex, 16).unwrap();
             let dir_hex = match color.chars().last().unwrap() {
-                '0' => Direction::East,
-                '1' => Direction::South,
-                '2' => Direction::West,
-                '3' => Direction::North,
+                '0' => Dir4::East,
+                '1' => Dir4::South,
+                '2' => Dir4::West,
+                '3' => Dir4::North,
                 x => panic!("{x}")
             };
 
@@ -59,13 +53,12 @@ impl Day<Data> for Day18 {
     }
 }
 
-fn solve<'a, IT: Iterator<Item=(&'a Direction, i64)>>(iter: IT) -> i64 {
+fn solve<'a, IT: Iterator<Item=(&'a Dir4, i64)>>(iter: IT) -> i64 {
     let mut pos = (0, 0);
     let mut vertices = vec![pos];
     let mut perimeter = 0;
     for (dir, dist) in iter {
-        let ds = dir.unit_i(dist);
-        pos = (pos.0 + ds.0, pos.1 + ds.1);
+        pos = (Vector::from(pos) + dir.unit() * dist).into();
         vertices.push(pos);
         perimeter += dist;
     }
@@ -5,12 +5,12 @@ use std::fmt::{Display, Formatter};
 use std::ops::Range;
 use std::str::FromStr;
 use itertools::FoldWhile::Continue;
-use itertools::{FoldWhile, Itertools};
+use itertools::FoldWhile;
 use regex::Regex;
 use crate::combinations::CombinationIterator;
 use crate::day5::Map;
 use crate::day::Day;
-use crate::ranges::{intersect, RangeD};
+use crate::ranges::{intersect, BoxSet, RangeD};
 
 pub struct Day19;
 
@@ -213,16 +213,8 @@ fn total(ranges: [Range<usize>; 4]) -> usize {
     ranges.into_iter().map(|r| r.len()).product()
 }
 
-fn inclusion_exclusion(ranges: &Vec<RangeD<4>>) -> usize {
-    (0..ranges.len()).map(|i| {
-        let x = ranges.iter().combinations(i + 1).filter_map(|mut s| {
-            let intersect = s.pop().unwrap().to_owned();
-            s.iter().try_fold(intersect, |i, s| i.intersect(s))
-                .map(|x| x.volume())
-        }).sum::<usize>() as i64 * if i % 2 == 0 { 1 } else { -1 };
-        // dbg!(i);
-        dbg!(x)
-    }).sum::<i64>() as usize
+fn union_volume(ranges: &[RangeD<usize, 4>]) -> usize {
+    ranges.iter().cloned().collect::<BoxSet<usize, 4>>().volume()
 }
 
 impl Day<Data> for Day19 {
@@ -3,12 +3,14 @@ pub struct Day20;
 
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::{Display, Formatter};
+use std::path::Path;
 use std::{thread, time};
 use colored::Colorize;
 use itertools::{Itertools, join};
 use num::Integer;
-use pathfinding::prelude::{bfs_reach, brent, topological_sort};
+use pathfinding::prelude::{bfs_reach, topological_sort};
 use crate::day::Day;
+use crate::graph::{Dot, Graph};
 
 #[derive(Eq, PartialEq, Clone)]
 #[derive(Debug)]
@@ -125,6 +127,28 @@ impl Module {
 
 pub type Data = HashMap<String, Module>;
 
+fn module_graph(data: &Data) -> Graph<&str, ()> {
+    Graph::from_adjacency(
+        data.values()
+            .sorted_by_key(|m| &m.label)
+            .map(|m| (m.label.as_str(), m.destinations.iter().map(|d| (d.as_str(), ())))),
+        true,
+    )
+}
+
+// graphviz view of the network, outputs without a module (like rx) are drawn as plain text
+fn module_dot<'a>(graph: &'a Graph<&'a str, ()>, data: &'a Data) -> Dot<'a, &'a str, ()> {
+    graph.dot()
+        .name("modules")
+        .labels(|_, n| data.get(*n).map(Module::label_fmt).unwrap_or(n.to_string()))
+        .shapes(|_, n| match data.get(*n).map(|m| &m.m_type) {
+            Some(ModuleType::Broadcaster) => "doublecircle",
+            Some(ModuleType::FlipFlop { .. }) => "box",
+            Some(ModuleType::Conjunction { .. }) => "diamond",
+            None => "plaintext",
+        }.to_owned())
+}
+
 fn step_modules(mut data: Data) -> (Data, (usize, usize)) {
     let mut pulses = VecDeque::from([("broadcaster".to_owned(), Pulse::new("broadcaster".to_owned(), false))]);
     let mut low = 0;
@@ -259,4 +283,20 @@ impl Day<Data> for Day20 {
         
         panic!("Exited without finding rx?")
     }
-}
\ No newline at end of file
+
+    fn write_dot(&self, data: &Data, dir: &Path) -> std::io::Result<()> {
+        module_dot(&module_graph(data), data).save(dir.join("modules.dot"))
+    }
+}
+
+#[test]
+fn test_module_dot() {
+    let data = Day20.parse_file("broadcaster -> a\n%a -> inv, con\n&inv -> b\n%b -> con\n&con -> output".to_owned());
+    let dot = module_dot(&module_graph(&data), &data).to_string();
+    assert!(dot.starts_with("digraph \"modules\" {\n"));
+    assert!(dot.contains("[label=\"broadcaster\", shape=doublecircle];"));
+    assert!(dot.contains("[label=\"%a\", shape=box];"));
+    assert!(dot.contains("[label=\"&con\", shape=diamond];"));
+    assert!(dot.contains("[label=\"output\", shape=plaintext];"));
+    assert_eq!(dot.matches(" -> ").count(), 6);
+}
@@ -1,5 +1,7 @@
 ﻿use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::iter::repeat;
+use colored::Color;
 use horner::eval_known_rank_polynomial;
 use indexmap::{indexset, IndexSet};
 use itertools::Itertools;
@@ -19,7 +21,20 @@ pub enum Tile {
     Rocks
 }
 
+impl Display for Tile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.glyph())
+    }
+}
+
 impl Tile {
+    pub fn glyph(&self) -> char {
+        match self {
+            Tile::Garden => '.',
+            Tile::Rocks => '#',
+        }
+    }
+
     pub fn is_garden(&self) -> bool {
         match self {
             Tile::Garden => true,
@@ -152,18 +167,13 @@ fn step_positions(mut positions: IndexSet<(usize, usize)>, grid: &Grid<Tile>) ->
 
 fn print_grid(grid: &Grid<Tile>, positions: &IndexSet<(usize, usize)>) {
     print!("\x1B[2J\x1B[1;1H");
-    for y in 0..grid.h {
-        for x in 0..grid.w {
-            print!("{}", if positions.contains(&(x, y)) { "O" } else {
-                match grid.get(x, y).unwrap() {
-                    Tile::Garden => ".",
-                    Tile::Rocks => "#"
-                }
-            });
-        }
-        println!();
-    }
-    println!();
+    // reached cells keep their 'O' so they still show up without colour
+    let marked: Grid<char> = (0..grid.h)
+        .map(|y| (0..grid.w).map(move |x| {
+            if positions.contains(&(x, y)) { 'O' } else { grid.get(x, y).unwrap().glyph() }
+        }))
+        .collect();
+    println!("{}", marked.highlighted().predicate(|_, c| *c == 'O', Color::Green));
 }
 
 fn print_grid_cycle(grid: &Grid<Tile>, positions: &IndexSet<(i64, i64)>) {
@@ -175,18 +185,11 @@ fn print_grid_cycle(grid: &Grid<Tile>, positions: &IndexSet<(i64, i64)>) {
     x_range.end = x_range.end.max(grid.w as i64);
     y_range.start = y_range.start.min(0);
     y_range.end = y_range.end.max(grid.h as i64);
-    // dbg!(&x_range, &y_range);
     
-    for y in y_range {
-        for x in x_range.clone() {
-            print!("{}", if positions.contains(&(x, y)) { "O" } else {
-                match grid.get_cycle(x, y).unwrap() {
-                    Tile::Garden => ".",
-                    Tile::Rocks => "#"
-                }
-            });
-        }
-        println!();
-    }
-    println!();
-}
\ No newline at end of file
+    let window: Grid<char> = y_range
+        .map(|y| x_range.clone().map(move |x| {
+            if positions.contains(&(x, y)) { 'O' } else { grid.get_cycle(x, y).unwrap().glyph() }
+        }))
+        .collect();
+    println!("{}", window.highlighted().predicate(|_, c| *c == 'O', Color::Green));
+}
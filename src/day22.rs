@@ -1,11 +1,14 @@
 ﻿use std::collections::{HashMap, HashSet};
 use std::collections::hash_map::RandomState;
 use crate::day::Day;
+use crate::graph::Graph;
+use crate::grid::GridN;
 use crate::ranges::{min_max_comp, RangeD};
+use crate::spatial::BoxIndex;
 
 pub struct Day22;
 
-pub type Data = Vec<RangeD<3>>;
+pub type Data = Vec<RangeD<usize, 3>>;
 
 impl Day<Data> for Day22 {
     fn parse_file(&self, file_content: String) -> Data {
@@ -35,44 +38,10 @@ impl Day<Data> for Day22 {
         println!();
         
         blocks.sort_unstable_by_key(|b| b.start[2]);
-        let mut grid_map = HashMap::new();
+        let index = settle_blocks(&blocks);
+        print_slices(&index);
         
-        for i in 0..blocks.len() {
-            let mut block = blocks.get(i).unwrap().clone();
-            while !block.iter().any(|p| grid_map.contains_key(&p)) && &block.start[2] != &0 {
-                block.offset_component_neg(2, 1);
-            }
-            block.offset_component(2, 1);
-            
-            for p in block.iter() {
-                grid_map.insert(p, i);
-            }
-        }
-        
-        let (mins, maxes) = min_max_comp(grid_map.keys().cloned()).unwrap();
-        for x in mins[0]..=maxes[0] {
-            println!("\nx={x}");
-            for z in (mins[2]..=maxes[2]).rev() {
-                for y in mins[1]..=maxes[1] {
-                    print!("{}", grid_map.get(&[x,y,z]).map(|i| i.to_string())
-                        .unwrap_or(".".to_string()));
-                }
-                println!();
-            }
-        }
-        
-        let mut supports = HashMap::<usize, HashSet<usize>>::new();
-        
-        for (pos, id) in grid_map.iter() {
-            let pos = [pos[0], pos[1], pos[2] - 1];
-            if let Some(i) = grid_map.get(&pos) {
-                if i == id { continue; }
-                
-                supports.entry(*id)
-                    .and_modify(|x| { x.insert(*i); })
-                    .or_insert(HashSet::from([*i]));
-            }
-        }
+        let supports = supports(&index);
         
         // let x = supports.values().filter(|s| s.len() != 1).count();
         let mut supported_blocks: HashSet<_, RandomState> = HashSet::from_iter((0..blocks.len()));
@@ -91,7 +60,7 @@ impl Day<Data> for Day22 {
         //     println!("{}", block);
         // }
         
-        // let mut supported_blocks: HashSet<RangeD<3>, RandomState> = HashSet::from_iter(blocks.iter().cloned());
+        // let mut supported_blocks: HashSet<RangeD<usize, 3>, RandomState> = HashSet::from_iter(blocks.iter().cloned());
         // 
         // for (i, b) in blocks.iter().enumerate() {
         //     let mut block = b.clone();
@@ -118,109 +87,65 @@ impl Day<Data> for Day22 {
         println!();
 
         blocks.sort_unstable_by_key(|b| b.start[2]);
-        let mut grid_map = HashMap::new();
+        let index = settle_blocks(&blocks);
+        print_slices(&index);
 
-        for i in 0..blocks.len() {
-            let mut block = blocks.get(i).unwrap().clone();
-            while !block.iter().any(|p| grid_map.contains_key(&p)) && &block.start[2] != &0 {
-                block.offset_component_neg(2, 1);
-            }
-            block.offset_component(2, 1);
+        let supports = supports(&index);
 
-            for p in block.iter() {
-                grid_map.insert(p, i);
-            }
+        // the ground is the root, a block falls once every path from the ground to it
+        // goes through the removed block, so the dominator tree answers it in one go
+        let mut graph: Graph<Option<usize>, ()> = Graph::directed();
+        let ground = graph.add_node(None);
+        for id in 0..blocks.len() {
+            graph.add_node(Some(id));
         }
-
-        let (mins, maxes) = min_max_comp(grid_map.keys().cloned()).unwrap();
-        for x in mins[0]..=maxes[0] {
-            println!("\nx={x}");
-            for z in (mins[2]..=maxes[2]).rev() {
-                for y in mins[1]..=maxes[1] {
-                    print!("{}", grid_map.get(&[x,y,z]).map(|i| i.to_string())
-                        .unwrap_or(".".to_string()));
-                }
-                println!();
+        for id in 0..blocks.len() {
+            match supports.get(&id) {
+                Some(below) => below.iter().for_each(|b| graph.add_edge(Some(*b), Some(id), ())),
+                None => graph.add_edge(None, Some(id), ()),
             }
         }
-
-        let mut supports = HashMap::<usize, HashSet<usize>>::new();
-
-        for (pos, id) in grid_map.iter() {
-            let pos = [pos[0], pos[1], pos[2] - 1];
-            if let Some(i) = grid_map.get(&pos) {
-                if i == id { continue; }
-
-                supports.entry(*id)
-                    .and_modify(|x| { x.insert(*i); })
-                    .or_insert(HashSet::from([*i]));
-            }
-        }
-
-        // let x = supports.values().filter(|s| s.len() != 1).count();
-        let mut supported_blocks: HashSet<_, RandomState> = HashSet::from_iter((0..blocks.len()));
         
-        dbg!(&supports);
-        
-        let mut s = HashSet::new();
+        graph.cut_off_if_removed(ground).into_iter().skip(1).sum::<usize>() as i64
+    }
+}
 
-        for x in supports.values().filter_map(|s| {
-            if s.len() == 1 {
-                Some(s.iter().next().unwrap())
-            } else {
-                None
-            }
-        }) {
-            s.insert(x);
-            supported_blocks.remove(x);
-        }
-        
-        dbg!(&supported_blocks);
-        dbg!(&s);
-        
-        let mut sum = 0;
-        for base_blocks in s {
-            dbg!(base_blocks);
-            let mut to_remove = vec![*base_blocks];
-            let mut supports = supports.clone();
-            let mut removed = HashSet::new();
-            while let Some(b) = to_remove.pop() {
-                removed.insert(b);
-                for (id, set) in supports.iter_mut()
-                    .filter(|(i, _)| !removed.contains(i)) {
-                    set.remove(&b);
-                    if set.len() == 0 {
-                        to_remove.push(*id);
-                    }
-                }
-                
-                // dbg!(b, &supports);
-            }
-            sum += removed.len() - 1;
-        }
-        
-        sum as i64
+// drops the blocks (sorted by z) in order, ids in the index match the order of `blocks`
+fn settle_blocks(blocks: &[RangeD<usize, 3>]) -> BoxIndex<usize, 3> {
+    let mut index = BoxIndex::new(4);
 
-        // for block in blocks.iter() {
-        //     println!("{}", block);
-        // }
+    for block in blocks.iter() {
+        let mut block = block.clone();
+        // z = 0 is the ground
+        let floor = index.highest_below(&block, 2).unwrap_or(1);
+        block.offset_component_neg(2, block.start[2] - floor);
 
-        // let mut supported_blocks: HashSet<RangeD<3>, RandomState> = HashSet::from_iter(blocks.iter().cloned());
-        // 
-        // for (i, b) in blocks.iter().enumerate() {
-        //     let mut block = b.clone();
-        //     
-        //     let supporting_blocks = blocks[..i.saturating_sub(1)]
-        //         .iter().filter(|r| block.intersects(r)).chain(blocks[i+1..]
-        //         .iter().filter(|r| block.intersects(r))).collect::<Vec<_>>();
-        //     
-        //     if supporting_blocks.len() == 1 {
-        //         supported_blocks.remove(supporting_blocks.first().unwrap());
-        //     }            
-        // }
-        // 
-        // dbg!(&supported_blocks);
+        index.insert(block);
+    }
+
+    index
+}
+
+// for every resting block, the blocks directly under it
+fn supports(index: &BoxIndex<usize, 3>) -> HashMap<usize, HashSet<usize>> {
+    index.iter()
+        .map(|(id, block)| (id, index.directly_below(block, 2)))
+        .filter(|(_, below)| !below.is_empty())
+        .map(|(id, below)| (id, below.into_iter().collect()))
+        .collect()
+}
+
+fn print_slices(index: &BoxIndex<usize, 3>) {
+    let (_, maxes) = min_max_comp(index.iter().map(|(_, b)| b.end)).unwrap();
+    let mut grid = GridN::new(maxes, None);
+    for (id, block) in index.iter() {
+        grid.fill_range(block, Some(id));
+    }
 
-        // supported_blocks.len() as i64
+    for x in 0..grid.dims[0] {
+        println!("\nx={x}");
+        print!("{}", grid.slice(1, 2, [x, 0, 0])
+            .rows_descending()
+            .render(|i| i.map(|i| i.to_string()).unwrap_or(".".to_string())));
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,9 @@
 ﻿use itertools::Itertools;
 use nalgebra::{Matrix2, Matrix2x1, Matrix3, Matrix3x1, Matrix4, Matrix4x1, Matrix5, Matrix5x1};
+use rayon::prelude::*;
+use crate::combinations::CombinationIterator;
 use crate::day::Day;
+use crate::vector::Vector;
 
 pub struct Day24;
 
@@ -21,9 +24,7 @@ impl Hail {
     }
     
     pub fn eval(&self, t: f64) -> [f64; 3] {
-        let mut output = self.pos;
-        output.iter_mut().zip(self.vel).for_each(|(s, v)| *s += v * t);
-        output
+        (Vector(self.pos) + Vector(self.vel) * t).into()
     }
     
     pub fn sx(&self) -> f64 { self.pos[0] }
@@ -63,8 +64,9 @@ impl Day<Vec<Hail>> for Day24 {
         let test_area = if data.len() == 5 { 7.0..=27.0} 
         else { 200000000000000.0..=400000000000000.0 };
         
-        data.iter().tuple_combinations()
-            .filter_map(|(a, b)| a.intersect_2d(b))
+        CombinationIterator::<_, 2>::new(data)
+            .into_par_iter()
+            .filter_map(|[a, b]| a.intersect_2d(b))
             .filter(|([x, y], u, v)|
                 u >= &0.0 && v >= &0.0 && test_area.contains(x) && test_area.contains(y))
             .count() as i64
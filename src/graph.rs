@@ -1,10 +1,16 @@
-﻿use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+﻿use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
 use std::hash::Hash;
-use std::ops::Add;
+use std::io::Write;
+use std::ops::{Range, Sub};
+use std::path::Path;
 use itertools::Itertools;
-use num::{One, Zero};
+use num::{PrimInt, ToPrimitive, Zero};
 use pathfinding::prelude::{bfs, dijkstra};
+use rand::Rng;
+use rayon::prelude::*;
+use crate::grid::save_with;
 
 pub fn all_expand<N, FN, IN>(start: N, mut successors: FN) -> Vec<HashSet<N>>
 where
@@ -31,72 +37,142 @@ where
     final_expansions
 }
 
-pub fn floyd_warshall<C>(adjacency_matrix: &[Vec<C>]) -> Vec<Vec<C>>
-    where
-        C: Zero + Ord + Copy + One,
-{
-    #[derive(Eq, PartialEq, PartialOrd, Copy, Clone)]
-    enum Grid<C: Ord + Add<Output = C>> { Some(C), Inf }
-    impl<C: Ord + Add<Output = C>> Add for Grid<C> {
-        type Output = Grid<C>;
+// all pairs shortest paths, `next[i][j]` is the node after i on a shortest path to j
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllPairs<C> {
+    pub dist: Vec<Vec<Option<C>>>,
+    pub next: Vec<Vec<Option<usize>>>,
+}
 
-        fn add(self, rhs: Self) -> Self::Output {
-            match (self, rhs) {
-                (Grid::Some(a), Grid::Some(b)) => Grid::Some(a + b),
-                (_, _) => Grid::Inf,
-            }
-        }
+// node ids around a cycle with negative total weight, no shortest paths exist through it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle(pub Vec<usize>);
+
+impl<C: Copy> AllPairs<C> {
+    pub fn distance(&self, from: usize, to: usize) -> Option<C> {
+        self.dist[from][to]
     }
-    impl<C: Ord + Add<Output = C>> Grid<C> {
-        pub fn unwrap_or_inf(self, def: C) -> C {
-            match self {
-                Grid::Some(c) => c,
-                Grid::Inf => def
-            }
+
+    // node ids from `from` to `to`, both included
+    pub fn path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        self.dist[from][to]?;
+        let mut path = vec![from];
+        let mut cur = from;
+        while cur != to {
+            cur = self.next[cur][to]?;
+            path.push(cur);
         }
+        Some(path)
     }
-    impl<C: Ord + Add<Output = C>> Ord for Grid<C> {
-        fn cmp(&self, other: &Self) -> Ordering {
-            match (self, other) {
-                (Grid::Some(a), Grid::Some(b)) => a.cmp(b),
-                (Grid::Some(_), Grid::Inf) => Ordering::Less,
-                (Grid::Inf, Grid::Some(_)) => Ordering::Greater,
-                (_, _) => Ordering::Equal,
-            }
-        }
+}
+
+// follows `step` from `start` until a node repeats and returns the loop it ended up in
+fn find_loop<FN: Fn(usize) -> usize>(start: usize, step: FN) -> Vec<usize> {
+    let mut seen = HashMap::new();
+    let mut order = vec![];
+    let mut cur = start;
+    while !seen.contains_key(&cur) {
+        seen.insert(cur, order.len());
+        order.push(cur);
+        cur = step(cur);
     }
+    order.split_off(seen[&cur])
+}
 
+pub fn floyd_warshall<C>(adjacency_matrix: &[Vec<Option<C>>]) -> Result<AllPairs<C>, NegativeCycle>
+    where
+        C: Zero + Ord + Copy,
+{
     let num_nodes = adjacency_matrix.len();
-    let mut dist_matrix = adjacency_matrix.into_iter().enumerate().map(|(i, s)| {
-        s.into_iter().enumerate().map(|(j, x)| {
-            if i == j || !x.is_zero() { Grid::Some(*x) } else { Grid::Inf }
-        }).collect_vec()
-    }).collect_vec();
+    let mut dist = adjacency_matrix.to_vec();
+    let mut next = adjacency_matrix.iter()
+        .map(|row| row.iter().enumerate().map(|(j, x)| x.map(|_| j)).collect_vec())
+        .collect_vec();
+    for i in 0..num_nodes {
+        if dist[i][i].is_none_or(|d| d > C::zero()) {
+            dist[i][i] = Some(C::zero());
+            next[i][i] = Some(i);
+        }
+    }
 
     for k in 0..num_nodes {
         for i in 0..num_nodes {
+            let Some(ik) = dist[i][k] else { continue; };
             for j in 0..num_nodes {
-                dist_matrix[i][j] = std::cmp::min(dist_matrix[i][j], dist_matrix[i][k] + dist_matrix[k][j]);
+                let Some(kj) = dist[k][j] else { continue; };
+                if dist[i][j].is_none_or(|ij| ik + kj < ij) {
+                    dist[i][j] = Some(ik + kj);
+                    next[i][j] = next[i][k];
+                }
             }
         }
+
+        // stop as soon as a cycle shows up, going on would keep pushing distances down until
+        // they overflow
+        if (0..num_nodes).any(|i| dist[i][i].is_some_and(|d| d < C::zero())) {
+            return Err(negative_cycle(adjacency_matrix));
+        }
     }
-    
-    dist_matrix.into_iter().map(|s| {
-        s.into_iter().map(|x| x.unwrap_or_inf(C::zero())).collect_vec()
-    }).collect_vec()
+
+    Ok(AllPairs { dist, next })
+}
+
+fn negative_cycle<C>(adjacency_matrix: &[Vec<Option<C>>]) -> NegativeCycle
+    where
+        C: Zero + Ord + Copy,
+{
+    // next hops are garbage around negative cycles, bellman ford gives a proper one
+    let edges = adjacency_matrix.iter().enumerate()
+        .flat_map(|(i, row)| row.iter().enumerate().filter_map(move |(j, w)| Some((i, j, (*w)?))))
+        .collect_vec();
+    match potentials(adjacency_matrix.len(), &edges) {
+        Err(cycle) => cycle,
+        Ok(_) => unreachable!("negative diagonal without a negative cycle"),
+    }
+}
+
+// bellman ford from a virtual source with a zero weight edge to every node
+fn potentials<C>(num_nodes: usize, edges: &[(usize, usize, C)]) -> Result<Vec<C>, NegativeCycle>
+    where
+        C: Zero + Ord + Copy,
+{
+    let mut h = vec![C::zero(); num_nodes];
+    let mut pred = vec![None; num_nodes];
+    for round in 0..=num_nodes {
+        let mut changed = None;
+        for &(a, b, w) in edges {
+            if h[a] + w < h[b] {
+                h[b] = h[a] + w;
+                pred[b] = Some(a);
+                changed = Some(b);
+            }
+        }
+        match changed {
+            None => return Ok(h),
+            // still relaxing after n rounds, walking back n steps is guaranteed to land on the cycle
+            Some(b) if round == num_nodes => {
+                let start = (0..num_nodes).fold(b, |cur, _| pred[cur].unwrap());
+                let mut cycle = find_loop(start, |cur| pred[cur].unwrap());
+                cycle.reverse();
+                return Err(NegativeCycle(cycle));
+            }
+            Some(_) => {}
+        }
+    }
+    unreachable!()
 }
 
 pub fn create_adjacency_matrix<N, C, FN, IN>(
     nodes: &[N],
     successors: FN,
-) -> (Vec<Vec<C>>, HashMap<N, usize>)
+) -> (Vec<Vec<Option<C>>>, HashMap<N, usize>)
     where
         N: Eq + Clone + Hash,
-        C: Zero + Ord + Copy + One,
+        C: Ord + Copy,
         FN: Fn(&N) -> IN,
         IN: IntoIterator<Item = (N, C)>,
 {
-    let mut adjacency_matrix = vec![vec![C::zero(); nodes.len()]; nodes.len()];
+    let mut adjacency_matrix = vec![vec![None; nodes.len()]; nodes.len()];
     let mut node_index_map: HashMap<N, usize> = HashMap::new();
 
     // Create a mapping from nodes to their indices
@@ -110,7 +186,9 @@ pub fn create_adjacency_matrix<N, C, FN, IN>(
 
         for (target_node, weight) in successors_iter {
             if let Some(&target_index) = node_index_map.get(&target_node) {
-                adjacency_matrix[source_index][target_index] = weight;
+                // keep the cheapest of any parallel edges
+                let cell: &mut Option<C> = &mut adjacency_matrix[source_index][target_index];
+                if cell.is_none_or(|c| weight < c) { *cell = Some(weight); }
             } else {
                 // Handle the case where the target node is not found in the provided nodes slice
                 panic!("Target node not found in the provided nodes slice");
@@ -131,7 +209,7 @@ pub fn test_adjacency_matrix() {
         _ => vec![],
     };
 
-    let (mut adjacency_matrix, node_index_map) = create_adjacency_matrix(&nodes, successors);
+    let (adjacency_matrix, node_index_map) = create_adjacency_matrix(&nodes, successors);
 
     // Print the adjacency matrix
     for row in &adjacency_matrix {
@@ -143,10 +221,10 @@ pub fn test_adjacency_matrix() {
         println!("Node: {}, Index: {}", node, index);
     }
     
-    adjacency_matrix = floyd_warshall(&adjacency_matrix);
+    let shortest = floyd_warshall(&adjacency_matrix).unwrap();
 
-    // Print the adjacency matrix
-    for row in &adjacency_matrix {
+    // Print the distance matrix
+    for row in &shortest.dist {
         println!("{:?}", row);
     }
 
@@ -155,3 +233,1188 @@ pub fn test_adjacency_matrix() {
         println!("Node: {}, Index: {}", node, index);
     }
 }
+
+// nodes are interned into dense ids in insertion order, edges are stored as
+// outgoing adjacency lists. undirected edges are stored in both lists
+#[derive(Debug, Clone)]
+pub struct Graph<N, E> {
+    nodes: Vec<N>,
+    ids: HashMap<N, usize>,
+    edges: Vec<Vec<(usize, E)>>,
+    edge_count: usize,
+    directed: bool,
+}
+
+impl<N, E> Graph<N, E>
+    where
+        N: Eq + Hash + Clone,
+{
+    pub fn new(directed: bool) -> Self {
+        Self {
+            nodes: vec![],
+            ids: HashMap::new(),
+            edges: vec![],
+            edge_count: 0,
+            directed,
+        }
+    }
+
+    pub fn directed() -> Self {
+        Self::new(true)
+    }
+
+    pub fn undirected() -> Self {
+        Self::new(false)
+    }
+
+    // explores everything reachable from `starts`. in undirected mode `successors` is expected to
+    // be symmetric, so an edge back to an already expanded node is skipped instead of doubled
+    pub fn from_successors<FN, IN>(starts: impl IntoIterator<Item = N>, mut successors: FN, directed: bool) -> Self
+        where
+            E: Clone,
+            FN: FnMut(&N) -> IN,
+            IN: IntoIterator<Item = (N, E)>,
+    {
+        let mut graph = Self::new(directed);
+        let mut to_visit = starts.into_iter().map(|n| graph.add_node(n)).collect_vec();
+        let mut expanded = HashSet::new();
+        while let Some(id) = to_visit.pop() {
+            if !expanded.insert(id) { continue; }
+            for (suc, weight) in successors(&graph.nodes[id].clone()) {
+                let suc = graph.add_node(suc);
+                if !directed && suc != id && expanded.contains(&suc) { continue; }
+                graph.add_edge_ids(id, suc, weight);
+                to_visit.push(suc);
+            }
+        }
+        graph
+    }
+
+    // every listed edge is added as is, nodes that only appear as targets are added too
+    pub fn from_adjacency<IT, IN>(lists: IT, directed: bool) -> Self
+        where
+            E: Clone,
+            IT: IntoIterator<Item = (N, IN)>,
+            IN: IntoIterator<Item = (N, E)>,
+    {
+        let mut graph = Self::new(directed);
+        for (node, targets) in lists {
+            let from = graph.add_node(node);
+            for (target, weight) in targets {
+                let to = graph.add_node(target);
+                graph.add_edge_ids(from, to, weight);
+            }
+        }
+        graph
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    // returns the existing id if the node is already known
+    pub fn add_node(&mut self, node: N) -> usize {
+        if let Some(&id) = self.ids.get(&node) { return id; }
+        let id = self.nodes.len();
+        self.ids.insert(node.clone(), id);
+        self.nodes.push(node);
+        self.edges.push(vec![]);
+        id
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N, weight: E) where E: Clone {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        self.add_edge_ids(from, to, weight);
+    }
+
+    pub fn add_edge_ids(&mut self, from: usize, to: usize, weight: E) where E: Clone {
+        if !self.directed && from != to {
+            self.edges[to].push((from, weight.clone()));
+        }
+        self.edges[from].push((to, weight));
+        self.edge_count += 1;
+    }
+
+    pub fn id(&self, node: &N) -> Option<usize> {
+        self.ids.get(node).copied()
+    }
+
+    pub fn node(&self, id: usize) -> &N {
+        &self.nodes[id]
+    }
+
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn ids(&self) -> Range<usize> {
+        0..self.nodes.len()
+    }
+
+    pub fn neighbours(&self, id: usize) -> &[(usize, E)] {
+        &self.edges[id]
+    }
+
+    pub fn degree(&self, id: usize) -> usize {
+        self.edges[id].len()
+    }
+
+    // every edge once, undirected edges from their lower id
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, &E)> {
+        self.edges.iter().enumerate().flat_map(move |(from, list)| {
+            list.iter()
+                .filter(move |(to, _)| self.directed || from <= *to)
+                .map(move |(to, weight)| (from, *to, weight))
+        })
+    }
+
+    // same graph with every edge pointing the other way, undirected graphs are returned as is
+    pub fn reversed(&self) -> Self where E: Clone {
+        if !self.directed { return self.clone(); }
+        let mut out = Self {
+            nodes: self.nodes.clone(),
+            ids: self.ids.clone(),
+            edges: vec![vec![]; self.nodes.len()],
+            edge_count: 0,
+            directed: true,
+        };
+        for (from, to, weight) in self.edges() {
+            out.add_edge_ids(to, from, weight.clone());
+        }
+        out
+    }
+
+    pub fn map_edges<X, FN: FnMut(usize, usize, &E) -> X>(&self, mut func: FN) -> Graph<N, X> {
+        Graph {
+            nodes: self.nodes.clone(),
+            ids: self.ids.clone(),
+            edges: self.edges.iter().enumerate()
+                .map(|(from, list)| list.iter().map(|(to, w)| (*to, func(from, *to, w))).collect())
+                .collect(),
+            edge_count: self.edge_count,
+            directed: self.directed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinCut<W> {
+    pub weight: W,
+    // node ids, `edges` go from the first side to the second
+    pub partition: (Vec<usize>, Vec<usize>),
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl<N, E> Graph<N, E>
+    where
+        N: Eq + Hash + Clone,
+{
+    fn min_cut_from_side<W>(&self, weight: W, side: &[usize]) -> MinCut<W> {
+        let mut in_side = vec![false; self.node_count()];
+        side.iter().for_each(|&i| in_side[i] = true);
+        let (first, second) = self.ids().partition(|&i| in_side[i]);
+        // a directed edge only counts if it leaves the first side
+        let edges = self.edges()
+            .filter(|(a, b, _)| in_side[*a] != in_side[*b] && (!self.directed || in_side[*a]))
+            .map(|(a, b, _)| if in_side[a] { (a, b) } else { (b, a) })
+            .collect();
+        MinCut {
+            weight,
+            partition: (first, second),
+            edges,
+        }
+    }
+
+    // global minimum cut of an undirected graph, None if there are less than two nodes.
+    // each phase grows a set from one node by always adding the most tightly connected node,
+    // the last two nodes added are then merged, so there are node_count - 1 phases
+    pub fn stoer_wagner<W, FN>(&self, weight: FN) -> Option<MinCut<W>>
+        where
+            W: Copy + Ord + Zero,
+            FN: Fn(&E) -> W,
+    {
+        assert!(!self.directed, "min cut needs an undirected graph");
+        if self.node_count() < 2 { return None; }
+
+        let mut adjacency: Vec<HashMap<usize, W>> = vec![HashMap::new(); self.node_count()];
+        for (a, b, e) in self.edges().filter(|(a, b, _)| a != b) {
+            let w = weight(e);
+            let ab = adjacency[a].entry(b).or_insert(W::zero());
+            *ab = *ab + w;
+            let ba = adjacency[b].entry(a).or_insert(W::zero());
+            *ba = *ba + w;
+        }
+        let mut groups = self.ids().map(|i| vec![i]).collect_vec();
+        let mut active = self.ids().collect_vec();
+        let mut best: Option<(W, Vec<usize>)> = None;
+
+        while active.len() > 1 {
+            let mut keys = vec![W::zero(); self.node_count()];
+            let mut added = vec![false; self.node_count()];
+            let mut heap: BinaryHeap<_> = active.iter().map(|&i| (W::zero(), i)).collect();
+            let (mut s, mut t) = (active[0], active[0]);
+            while let Some((key, node)) = heap.pop() {
+                // stale entry, the node was already added or its key has grown since
+                if added[node] || key != keys[node] { continue; }
+                added[node] = true;
+                (s, t) = (t, node);
+                for (&other, &w) in adjacency[node].iter() {
+                    if added[other] { continue; }
+                    keys[other] = keys[other] + w;
+                    heap.push((keys[other], other));
+                }
+            }
+
+            if best.as_ref().is_none_or(|(w, _)| keys[t] < *w) {
+                best = Some((keys[t], groups[t].clone()));
+            }
+
+            // merge t into s
+            for (other, w) in std::mem::take(&mut adjacency[t]) {
+                adjacency[other].remove(&t);
+                if other == s { continue; }
+                let so = adjacency[s].entry(other).or_insert(W::zero());
+                *so = *so + w;
+                let os = adjacency[other].entry(s).or_insert(W::zero());
+                *os = *os + w;
+            }
+            let merged = std::mem::take(&mut groups[t]);
+            groups[s].extend(merged);
+            active.retain(|&i| i != t);
+        }
+
+        best.map(|(w, side)| self.min_cut_from_side(w, &side))
+    }
+
+    // randomised recursive contraction, only finds the minimum cut with high probability so
+    // it keeps the best of `trials` runs. weights need to be non-negative
+    pub fn karger_stein<W, FN, R>(&self, weight: FN, trials: usize, rng: &mut R) -> Option<MinCut<W>>
+        where
+            W: Copy + Ord + Zero + ToPrimitive,
+            FN: Fn(&E) -> W,
+            R: Rng,
+    {
+        assert!(!self.directed, "min cut needs an undirected graph");
+        if self.node_count() < 2 { return None; }
+
+        let start = Contracted {
+            groups: self.ids().map(|i| vec![i]).collect(),
+            edges: self.edges().filter(|(a, b, _)| a != b).map(|(a, b, e)| (a, b, weight(e))).collect(),
+        };
+        (0..trials)
+            .map(|_| start.karger_stein(rng))
+            .min_by_key(|c| c.weight())
+            .map(|c| self.min_cut_from_side(c.weight(), &c.groups[0]))
+    }
+}
+
+// multigraph where every node stands for a group of original nodes
+#[derive(Clone)]
+struct Contracted<W> {
+    groups: Vec<Vec<usize>>,
+    edges: Vec<(usize, usize, W)>,
+}
+
+impl<W: Copy + Ord + Zero + ToPrimitive> Contracted<W> {
+    fn weight(&self) -> W {
+        self.edges.iter().fold(W::zero(), |s, (_, _, w)| s + *w)
+    }
+
+    fn karger_stein<R: Rng>(&self, rng: &mut R) -> Self {
+        let n = self.groups.len();
+        if n <= 6 {
+            return (0..n * n)
+                .map(|_| self.contract(2, rng))
+                .min_by_key(|c| c.weight())
+                .unwrap();
+        }
+        let target = (1.0 + n as f64 / std::f64::consts::SQRT_2).ceil() as usize;
+        (0..2)
+            .map(|_| self.contract(target, rng).karger_stein(rng))
+            .min_by_key(|c| c.weight())
+            .unwrap()
+    }
+
+    // contracting edges in a random order weighted by `w` is the same as giving each edge
+    // an exponential key with rate `w` and going through them like kruskal does
+    fn contract<R: Rng>(&self, target: usize, rng: &mut R) -> Self {
+        let mut order = self.edges.iter()
+            .map(|&(a, b, w)| {
+                let u: f64 = rng.gen_range(f64::EPSILON..1.0);
+                (-u.ln() / w.to_f64().unwrap(), a, b)
+            })
+            .collect_vec();
+        order.sort_unstable_by(|x, y| x.0.total_cmp(&y.0));
+
+        let mut parent = (0..self.groups.len()).collect_vec();
+        fn find(parent: &mut [usize], i: usize) -> usize {
+            if parent[i] != i { parent[i] = find(parent, parent[i]); }
+            parent[i]
+        }
+        let mut remaining = self.groups.len();
+        for (_, a, b) in order {
+            if remaining <= target { break; }
+            let (a, b) = (find(&mut parent, a), find(&mut parent, b));
+            if a != b {
+                parent[b] = a;
+                remaining -= 1;
+            }
+        }
+
+        let mut index = HashMap::new();
+        let mut groups: Vec<Vec<usize>> = vec![];
+        for i in 0..self.groups.len() {
+            let root = find(&mut parent, i);
+            let id = *index.entry(root).or_insert_with(|| { groups.push(vec![]); groups.len() - 1 });
+            groups[id].extend(self.groups[i].iter().copied());
+        }
+        let edges = self.edges.iter()
+            .map(|&(a, b, w)| (index[&find(&mut parent, a)], index[&find(&mut parent, b)], w))
+            .filter(|(a, b, _)| a != b)
+            .collect();
+        Self { groups, edges }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MaxFlow<N, C> {
+    pub value: C,
+    // directed, one edge per arc with capacity left. undirected edges count as an arc each way
+    pub residual: Graph<N, C>,
+    pub cut: MinCut<C>,
+}
+
+impl<N, E> Graph<N, E>
+    where
+        N: Eq + Hash + Clone,
+{
+    // dinic: blocking flows along bfs levels until the sink can't be reached
+    pub fn max_flow<C, FN>(&self, source: usize, sink: usize, capacity: FN) -> MaxFlow<N, C>
+        where
+            C: PrimInt,
+            FN: Fn(&E) -> C,
+    {
+        assert_ne!(source, sink, "source and sink must be different nodes");
+        let n = self.node_count();
+
+        // arc i ^ 1 is the reverse of arc i
+        let mut arcs: Vec<(usize, C)> = vec![];
+        let mut outgoing = vec![vec![]; n];
+        for (a, b, e) in self.edges().filter(|(a, b, _)| a != b) {
+            let c = capacity(e);
+            outgoing[a].push(arcs.len());
+            arcs.push((b, c));
+            outgoing[b].push(arcs.len());
+            arcs.push((a, if self.directed { C::zero() } else { c }));
+        }
+
+        fn levels<C: PrimInt>(arcs: &[(usize, C)], outgoing: &[Vec<usize>], source: usize) -> Vec<Option<usize>> {
+            let mut level = vec![None; outgoing.len()];
+            level[source] = Some(0);
+            let mut queue = VecDeque::from([source]);
+            while let Some(node) = queue.pop_front() {
+                for &arc in outgoing[node].iter() {
+                    let (to, cap) = arcs[arc];
+                    if cap > C::zero() && level[to].is_none() {
+                        level[to] = Some(level[node].unwrap() + 1);
+                        queue.push_back(to);
+                    }
+                }
+            }
+            level
+        }
+
+        fn push<C: PrimInt>(
+            node: usize, sink: usize, limit: C,
+            arcs: &mut [(usize, C)], outgoing: &[Vec<usize>], level: &[Option<usize>], next: &mut [usize],
+        ) -> C {
+            if node == sink { return limit; }
+            while next[node] < outgoing[node].len() {
+                let arc = outgoing[node][next[node]];
+                let (to, cap) = arcs[arc];
+                if cap > C::zero() && level[to] == level[node].map(|l| l + 1) {
+                    let pushed = push(to, sink, limit.min(cap), arcs, outgoing, level, next);
+                    if pushed > C::zero() {
+                        arcs[arc].1 = arcs[arc].1 - pushed;
+                        arcs[arc ^ 1].1 = arcs[arc ^ 1].1 + pushed;
+                        return pushed;
+                    }
+                }
+                next[node] += 1;
+            }
+            C::zero()
+        }
+
+        let mut value = C::zero();
+        let mut level = levels(&arcs, &outgoing, source);
+        while level[sink].is_some() {
+            let mut next = vec![0; n];
+            loop {
+                let pushed = push(source, sink, C::max_value(), &mut arcs, &outgoing, &level, &mut next);
+                if pushed.is_zero() { break; }
+                value = value + pushed;
+            }
+            level = levels(&arcs, &outgoing, source);
+        }
+
+        let mut residual = Graph {
+            nodes: self.nodes.clone(),
+            ids: self.ids.clone(),
+            edges: vec![vec![]; n],
+            edge_count: 0,
+            directed: true,
+        };
+        for (from, list) in outgoing.iter().enumerate() {
+            for &arc in list.iter().filter(|&&arc| arcs[arc].1 > C::zero()) {
+                residual.add_edge_ids(from, arcs[arc].0, arcs[arc].1);
+            }
+        }
+
+        // whatever the source can still reach is its side of the cut
+        let side = self.ids().filter(|&i| level[i].is_some()).collect_vec();
+        let cut = self.min_cut_from_side(value, &side);
+        MaxFlow { value, residual, cut }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LongestPath<C> {
+    pub length: C,
+    // node ids from start to end, both included
+    pub path: Vec<usize>,
+}
+
+// visited sets are u64 masks, so graphs can have at most 64 nodes
+struct LongestPathSearch<C> {
+    adjacency: Vec<Vec<(usize, C)>>,
+    out_masks: Vec<u64>,
+    end: usize,
+}
+
+impl<C> LongestPathSearch<C>
+    where
+        C: Copy + Ord + Zero,
+{
+    // everything reachable from `node` without going through `visited`
+    fn reachable(&self, node: usize, visited: u64) -> u64 {
+        let mut seen = 1 << node;
+        let mut frontier = seen;
+        while frontier != 0 {
+            let mut next = 0;
+            let mut bits = frontier;
+            while bits != 0 {
+                next |= self.out_masks[bits.trailing_zeros() as usize];
+                bits &= bits - 1;
+            }
+            frontier = next & !seen & !visited;
+            seen |= frontier;
+        }
+        seen
+    }
+
+    fn search(&self, node: usize, visited: u64, length: C, path: &mut Vec<usize>, best: &mut Option<LongestPath<C>>) {
+        if node == self.end {
+            if best.as_ref().is_none_or(|b| length > b.length) {
+                *best = Some(LongestPath { length, path: path.clone() });
+            }
+            return;
+        }
+        if self.reachable(node, visited) & (1 << self.end) == 0 { return; }
+
+        for &(next, c) in self.adjacency[node].iter() {
+            if visited & (1 << next) != 0 { continue; }
+            path.push(next);
+            self.search(next, visited | 1 << next, length + c, path, best);
+            path.pop();
+        }
+    }
+
+    fn finish(&self, prefix: &Prefix<C>) -> Option<LongestPath<C>> {
+        let mut best = None;
+        let mut path = prefix.path.clone();
+        self.search(*path.last().unwrap(), prefix.visited, prefix.length, &mut path, &mut best);
+        best
+    }
+
+    // grows the search tree from `start` a level at a time until there are at least `count`
+    // open prefixes, so a forced chain of single exits doesn't end up as one big task
+    fn branches(&self, start: usize, count: usize) -> Vec<Prefix<C>> {
+        let mut prefixes = vec![Prefix { path: vec![start], visited: 1 << start, length: C::zero() }];
+        while prefixes.len() < count {
+            let mut grown = false;
+            let mut next = vec![];
+            for prefix in prefixes {
+                let node = *prefix.path.last().unwrap();
+                if node == self.end {
+                    next.push(prefix);
+                    continue;
+                }
+                if self.reachable(node, prefix.visited) & (1 << self.end) == 0 { continue; }
+                grown = true;
+                for &(to, c) in self.adjacency[node].iter() {
+                    if prefix.visited & (1 << to) != 0 { continue; }
+                    let mut path = prefix.path.clone();
+                    path.push(to);
+                    next.push(Prefix { path, visited: prefix.visited | 1 << to, length: prefix.length + c });
+                }
+            }
+            prefixes = next;
+            if !grown { break; }
+        }
+        prefixes
+    }
+}
+
+// a partial path the parallel search hands to one thread
+struct Prefix<C> {
+    path: Vec<usize>,
+    visited: u64,
+    length: C,
+}
+
+impl<N, E> Graph<N, E>
+    where
+        N: Eq + Hash + Clone,
+{
+    fn longest_path_search<C, FN: Fn(&E) -> C>(&self, end: usize, weight: FN) -> LongestPathSearch<C> {
+        assert!(self.node_count() <= 64, "longest path needs at most 64 nodes, got {}", self.node_count());
+        let adjacency = self.edges.iter()
+            .map(|list| list.iter().map(|(to, e)| (*to, weight(e))).collect_vec())
+            .collect_vec();
+        let out_masks = adjacency.iter()
+            .map(|list| list.iter().fold(0, |m, (to, _)| m | 1 << to))
+            .collect();
+        LongestPathSearch { adjacency, out_masks, end }
+    }
+
+    // longest simple path by exhaustive dfs, cutting branches that can no longer reach `end`
+    pub fn longest_path<C, FN>(&self, start: usize, end: usize, weight: FN) -> Option<LongestPath<C>>
+        where
+            C: Copy + Ord + Zero,
+            FN: Fn(&E) -> C,
+    {
+        let search = self.longest_path_search(end, weight);
+        search.branches(start, 1).iter().find_map(|p| search.finish(p))
+    }
+
+    // same as `longest_path`, with the first few levels of the search split up between threads
+    pub fn par_longest_path<C, FN>(&self, start: usize, end: usize, weight: FN) -> Option<LongestPath<C>>
+        where
+            C: Copy + Ord + Zero + Send + Sync,
+            FN: Fn(&E) -> C,
+    {
+        if start == end { return Some(LongestPath { length: C::zero(), path: vec![start] }); }
+        let search = self.longest_path_search(end, weight);
+        search.branches(start, rayon::current_num_threads() * 4).par_iter()
+            .filter_map(|p| search.finish(p))
+            .max_by_key(|p| p.length)
+    }
+}
+
+impl<N, E> Graph<N, E>
+    where
+        N: Eq + Hash + Clone,
+{
+    // None where there's no edge, parallel edges keep the cheapest weight
+    pub fn adjacency_matrix<C, FN>(&self, weight: FN) -> Vec<Vec<Option<C>>>
+        where
+            C: Copy + Ord,
+            FN: Fn(&E) -> C,
+    {
+        let mut matrix = vec![vec![None; self.node_count()]; self.node_count()];
+        for (from, list) in self.edges.iter().enumerate() {
+            for (to, e) in list.iter() {
+                let w = weight(e);
+                let cell: &mut Option<C> = &mut matrix[from][*to];
+                if cell.is_none_or(|c| w < c) { *cell = Some(w); }
+            }
+        }
+        matrix
+    }
+
+    pub fn floyd_warshall<C, FN>(&self, weight: FN) -> Result<AllPairs<C>, NegativeCycle>
+        where
+            C: Zero + Ord + Copy,
+            FN: Fn(&E) -> C,
+    {
+        floyd_warshall(&self.adjacency_matrix(weight))
+    }
+
+    // reweights every edge to be non-negative using bellman ford potentials, then runs
+    // dijkstra from every node. much faster than floyd warshall on sparse graphs
+    pub fn johnson<C, FN>(&self, weight: FN) -> Result<AllPairs<C>, NegativeCycle>
+        where
+            C: Zero + Ord + Copy + Sub<Output = C>,
+            FN: Fn(&E) -> C,
+    {
+        let n = self.node_count();
+        let weight = &weight;
+        let edges = self.edges.iter().enumerate()
+            .flat_map(|(a, list)| list.iter().map(move |(b, e)| (a, *b, weight(e))))
+            .collect_vec();
+        let h = potentials(n, &edges)?;
+
+        let mut adjacency = vec![vec![]; n];
+        for (a, b, w) in edges {
+            adjacency[a].push((b, w + h[a] - h[b]));
+        }
+
+        let mut dist = vec![vec![None; n]; n];
+        let mut next = vec![vec![None; n]; n];
+        for s in 0..n {
+            let mut best: Vec<Option<C>> = vec![None; n];
+            // first hop on the way from s
+            let mut first = vec![None; n];
+            best[s] = Some(C::zero());
+            first[s] = Some(s);
+            let mut heap = BinaryHeap::from([Reverse((C::zero(), s))]);
+            while let Some(Reverse((d, u))) = heap.pop() {
+                if best[u].is_some_and(|b| d > b) { continue; }
+                for &(v, w) in adjacency[u].iter() {
+                    let nd = d + w;
+                    if best[v].is_none_or(|b| nd < b) {
+                        best[v] = Some(nd);
+                        first[v] = if u == s { Some(v) } else { first[u] };
+                        heap.push(Reverse((nd, v)));
+                    }
+                }
+            }
+            for v in 0..n {
+                dist[s][v] = best[v].map(|d| d - h[s] + h[v]);
+                next[s][v] = first[v];
+            }
+        }
+        Ok(AllPairs { dist, next })
+    }
+}
+
+type NodeFmt<'a, N> = Box<dyn Fn(usize, &N) -> String + 'a>;
+type EdgeFmt<'a, E> = Box<dyn Fn(&E) -> String + 'a>;
+
+// graphviz view of a graph, built up like grid highlighting. where highlight sets
+// overlap the first one added wins
+pub struct Dot<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    name: String,
+    label: NodeFmt<'a, N>,
+    shape: Option<NodeFmt<'a, N>>,
+    weight: Option<EdgeFmt<'a, E>>,
+    node_colors: Vec<(HashSet<usize>, String)>,
+    edge_colors: Vec<(HashSet<(usize, usize)>, String)>,
+}
+
+impl<N, E> Graph<N, E> {
+    pub fn dot(&self) -> Dot<'_, N, E> {
+        Dot {
+            graph: self,
+            name: "G".to_owned(),
+            label: Box::new(|id, _| id.to_string()),
+            shape: None,
+            weight: None,
+            node_colors: vec![],
+            edge_colors: vec![],
+        }
+    }
+}
+
+impl<'a, N: Eq + Hash + Clone, E> Dot<'a, N, E> {
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_owned();
+        self
+    }
+
+    pub fn labels<FN: Fn(usize, &N) -> String + 'a>(mut self, label: FN) -> Self {
+        self.label = Box::new(label);
+        self
+    }
+
+    pub fn shapes<FN: Fn(usize, &N) -> String + 'a>(mut self, shape: FN) -> Self {
+        self.shape = Some(Box::new(shape));
+        self
+    }
+
+    pub fn weights<FN: Fn(&E) -> String + 'a>(mut self, weight: FN) -> Self {
+        self.weight = Some(Box::new(weight));
+        self
+    }
+
+    pub fn highlight_nodes<IT: IntoIterator<Item = usize>>(mut self, nodes: IT, color: &str) -> Self {
+        self.node_colors.push((nodes.into_iter().collect(), color.to_owned()));
+        self
+    }
+
+    // in undirected graphs either direction matches
+    pub fn highlight_edges<IT: IntoIterator<Item = (usize, usize)>>(mut self, edges: IT, color: &str) -> Self {
+        let directed = self.graph.directed;
+        let edges = edges.into_iter()
+            .flat_map(|(a, b)| if directed { vec![(a, b)] } else { vec![(a, b), (b, a)] })
+            .collect();
+        self.edge_colors.push((edges, color.to_owned()));
+        self
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        save_with(path, |writer| write!(writer, "{}", self))
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl<'a, N: Eq + Hash + Clone, E> Display for Dot<'a, N, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (kind, arrow) = if self.graph.directed { ("digraph", "->") } else { ("graph", "--") };
+        writeln!(f, "{} \"{}\" {{", kind, dot_escape(&self.name))?;
+
+        for (id, node) in self.graph.nodes.iter().enumerate() {
+            let mut attrs = vec![format!("label=\"{}\"", dot_escape(&(self.label)(id, node)))];
+            if let Some(shape) = &self.shape {
+                attrs.push(format!("shape={}", shape(id, node)));
+            }
+            if let Some((_, color)) = self.node_colors.iter().find(|(set, _)| set.contains(&id)) {
+                attrs.push(format!("color={color}, style=bold"));
+            }
+            writeln!(f, "  n{} [{}];", id, attrs.join(", "))?;
+        }
+
+        for (from, to, e) in self.graph.edges() {
+            let mut attrs = vec![];
+            if let Some(weight) = &self.weight {
+                attrs.push(format!("label=\"{}\"", dot_escape(&weight(e))));
+            }
+            if let Some((_, color)) = self.edge_colors.iter().find(|(set, _)| set.contains(&(from, to))) {
+                attrs.push(format!("color={color}, penwidth=3"));
+            }
+            if attrs.is_empty() {
+                writeln!(f, "  n{from} {arrow} n{to};")?;
+            } else {
+                writeln!(f, "  n{from} {arrow} n{to} [{}];", attrs.join(", "))?;
+            }
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+// immediate dominators from a root: every path from the root to a node passes through its
+// dominators, so removing a node cuts off exactly the nodes it dominates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DominatorTree {
+    pub root: usize,
+    // None for the root and for nodes the root can't reach
+    idom: Vec<Option<usize>>,
+    // reachable nodes in reverse postorder, parents always come before children
+    order: Vec<usize>,
+}
+
+impl DominatorTree {
+    pub fn idom(&self, node: usize) -> Option<usize> {
+        self.idom[node]
+    }
+
+    pub fn dominates(&self, a: usize, mut b: usize) -> bool {
+        if !self.is_reachable(b) { return false; }
+        loop {
+            if a == b { return true; }
+            match self.idom[b] {
+                Some(parent) => b = parent,
+                None => return false,
+            }
+        }
+    }
+
+    pub fn is_reachable(&self, node: usize) -> bool {
+        node == self.root || self.idom[node].is_some()
+    }
+
+    // size of every node's subtree, itself included. 0 for unreachable nodes
+    pub fn subtree_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.idom.len()];
+        for &node in self.order.iter().rev() {
+            sizes[node] += 1;
+            if let Some(parent) = self.idom[node] {
+                sizes[parent] += sizes[node];
+            }
+        }
+        sizes
+    }
+
+    // nodes strictly dominated by `node`
+    pub fn dominated(&self, node: usize) -> Vec<usize> {
+        self.order.iter().copied().filter(|&n| n != node && self.dominates(node, n)).collect()
+    }
+}
+
+impl<N, E> Graph<N, E>
+    where
+        N: Eq + Hash + Clone,
+{
+    pub(crate) fn predecessors(&self) -> Vec<Vec<usize>> {
+        let mut preds = vec![vec![]; self.node_count()];
+        for (from, list) in self.edges.iter().enumerate() {
+            for (to, _) in list.iter() {
+                preds[*to].push(from);
+            }
+        }
+        preds
+    }
+
+    // reachable nodes in postorder of an iterative dfs
+    fn postorder(&self, root: usize) -> Vec<usize> {
+        let mut seen = vec![false; self.node_count()];
+        let mut order = vec![];
+        let mut stack = vec![(root, 0)];
+        seen[root] = true;
+        while let Some((node, i)) = stack.pop() {
+            if let Some(&(next, _)) = self.edges[node].get(i) {
+                stack.push((node, i + 1));
+                if !seen[next] {
+                    seen[next] = true;
+                    stack.push((next, 0));
+                }
+            } else {
+                order.push(node);
+            }
+        }
+        order
+    }
+
+    // kahn's algorithm, ties broken by lowest id. on a cycle it returns the nodes it couldn't order
+    pub fn topological_sort(&self) -> Result<Vec<usize>, Vec<usize>> {
+        assert!(self.directed, "topological sort needs a directed graph");
+        let mut in_degree = vec![0; self.node_count()];
+        for (_, to, _) in self.edges() {
+            in_degree[to] += 1;
+        }
+        let mut ready: BinaryHeap<_> = self.ids().filter(|&i| in_degree[i] == 0).map(Reverse).collect();
+        let mut order = vec![];
+        while let Some(Reverse(node)) = ready.pop() {
+            order.push(node);
+            for (next, _) in self.edges[node].iter() {
+                in_degree[*next] -= 1;
+                if in_degree[*next] == 0 {
+                    ready.push(Reverse(*next));
+                }
+            }
+        }
+        if order.len() == self.node_count() {
+            Ok(order)
+        } else {
+            Err(self.ids().filter(|&i| in_degree[i] > 0).collect())
+        }
+    }
+
+    // tarjan, iterative. components come out in reverse topological order
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        let n = self.node_count();
+        let mut index = vec![None; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = vec![];
+        let mut components = vec![];
+        let mut counter = 0;
+
+        for root in self.ids() {
+            if index[root].is_some() { continue; }
+            let mut calls = vec![(root, 0)];
+            while let Some((node, i)) = calls.pop() {
+                if i == 0 {
+                    index[node] = Some(counter);
+                    low[node] = counter;
+                    counter += 1;
+                    stack.push(node);
+                    on_stack[node] = true;
+                }
+                if let Some(&(next, _)) = self.edges[node].get(i) {
+                    calls.push((node, i + 1));
+                    match index[next] {
+                        None => calls.push((next, 0)),
+                        Some(j) if on_stack[next] => low[node] = low[node].min(j),
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+                // done with node, pass its low link up to whoever called it
+                if let Some(&(parent, _)) = calls.last() {
+                    low[parent] = low[parent].min(low[node]);
+                }
+                if Some(low[node]) == index[node] {
+                    let mut component = vec![];
+                    while let Some(x) = stack.pop() {
+                        on_stack[x] = false;
+                        component.push(x);
+                        if x == node { break; }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+        components
+    }
+
+    // cooper, harvey and kennedy's iterative algorithm
+    pub fn dominators(&self, root: usize) -> DominatorTree {
+        let mut order = self.postorder(root);
+        order.reverse();
+        let mut rank = vec![usize::MAX; self.node_count()];
+        for (i, &node) in order.iter().enumerate() {
+            rank[node] = i;
+        }
+        let preds = self.predecessors();
+
+        let mut idom = vec![None; self.node_count()];
+        idom[root] = Some(root);
+        let intersect = |idom: &[Option<usize>], mut a: usize, mut b: usize| {
+            while a != b {
+                while rank[a] > rank[b] { a = idom[a].unwrap(); }
+                while rank[b] > rank[a] { b = idom[b].unwrap(); }
+            }
+            a
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &node in order.iter().skip(1) {
+                let new_idom = preds[node].iter()
+                    .copied()
+                    .filter(|&p| idom[p].is_some())
+                    .reduce(|a, b| intersect(&idom, a, b));
+                if new_idom.is_some() && new_idom != idom[node] {
+                    idom[node] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+        idom[root] = None;
+        DominatorTree { root, idom, order }
+    }
+
+    // for every node, how many other nodes lose every path from `root` if it is removed
+    pub fn cut_off_if_removed(&self, root: usize) -> Vec<usize> {
+        self.dominators(root).subtree_sizes().into_iter().map(|s| s.saturating_sub(1)).collect()
+    }
+}
+
+#[test]
+fn test_graph_construction() {
+    let lists = [("a", vec![("b", 0), ("c", 2)]), ("b", vec![("c", 1)]), ("d", vec![])];
+    let directed: Graph<&str, i32> = Graph::from_adjacency(lists.clone(), true);
+    assert_eq!(directed.nodes(), &["a", "b", "c", "d"]);
+    assert_eq!(directed.edge_count(), 3);
+    // zero weights are still edges
+    assert_eq!(directed.neighbours(0), &[(1, 0), (2, 2)]);
+    assert_eq!(directed.neighbours(2), &[]);
+    assert_eq!(directed.reversed().neighbours(2), &[(0, 2), (1, 1)]);
+
+    let undirected: Graph<&str, i32> = Graph::from_adjacency(lists, false);
+    assert_eq!(undirected.neighbours(2), &[(0, 2), (1, 1)]);
+    assert_eq!(undirected.edges().count(), 3);
+
+    // symmetric successors on a 4-cycle, each edge should only be added once
+    let ring = Graph::from_successors([0], |&n: &i32| [((n + 1) % 4, 1), ((n + 3) % 4, 1)], false);
+    assert_eq!(ring.node_count(), 4);
+    assert_eq!(ring.edge_count(), 4);
+    assert!(ring.ids().all(|id| ring.degree(id) == 2));
+
+    let chain = Graph::from_successors(["x"], |&n: &&str| (n.len() < 3).then(|| (&"xxx"[..n.len() + 1], ())), true);
+    assert_eq!(chain.nodes(), &["x", "xx", "xxx"]);
+    assert_eq!(chain.id(&"xx"), Some(1));
+}
+
+#[test]
+fn test_min_cut() {
+    // two 4-cliques joined by two edges, plus a weight 5 edge inside the first clique
+    let mut graph = Graph::undirected();
+    for side in [0, 4] {
+        for (a, b) in (side..side + 4).tuple_combinations() {
+            graph.add_edge(a, b, 1);
+        }
+    }
+    graph.add_edge(0, 1, 5);
+    graph.add_edge(2, 6, 1);
+    graph.add_edge(3, 7, 1);
+
+    let cut = graph.stoer_wagner(|w| *w).unwrap();
+    assert_eq!(cut.weight, 2);
+    let mut sides = [cut.partition.0.clone(), cut.partition.1.clone()];
+    sides.sort();
+    assert_eq!(sides, [vec![0, 1, 2, 3], vec![4, 5, 6, 7]]);
+    assert_eq!(cut.edges.len(), 2);
+
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(25);
+    assert_eq!(graph.karger_stein(|w| *w, 10, &mut rng).unwrap().weight, 2);
+}
+
+#[test]
+fn test_max_flow() {
+    let graph: Graph<char, u32> = Graph::from_adjacency([
+        ('s', vec![('a', 10), ('b', 5)]),
+        ('a', vec![('b', 15), ('t', 4)]),
+        ('b', vec![('t', 10)]),
+    ], true);
+    let (s, t) = (graph.id(&'s').unwrap(), graph.id(&'t').unwrap());
+    let flow = graph.max_flow(s, t, |c| *c);
+    assert_eq!(flow.value, 14);
+    let mut cut = flow.cut.edges.iter().map(|&(a, b)| (*graph.node(a), *graph.node(b))).collect_vec();
+    cut.sort();
+    assert_eq!(cut, [('a', 't'), ('b', 't')]);
+    assert_eq!(flow.residual.neighbours(t).iter().map(|(_, c)| c).sum::<u32>(), 14);
+
+    // edge-disjoint paths in an undirected graph, opposite corners of a 3x3 grid
+    let grid = Graph::from_successors([(0, 0)], |&(x, y): &(i32, i32)| {
+        [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].into_iter()
+            .filter(|(x, y)| (0..3).contains(x) && (0..3).contains(y))
+            .map(|p| (p, 1))
+            .collect_vec()
+    }, false);
+    let flow = grid.max_flow(grid.id(&(0, 0)).unwrap(), grid.id(&(2, 2)).unwrap(), |c| *c);
+    assert_eq!(flow.value, 2);
+    assert_eq!(flow.cut.edges.len(), 2);
+}
+
+#[test]
+fn test_longest_path() {
+    // a 3x3 grid of nodes, the longest path between opposite corners visits every node
+    let grid = Graph::from_successors([(0, 0)], |&(x, y): &(i32, i32)| {
+        [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].into_iter()
+            .filter(|(x, y)| (0..3).contains(x) && (0..3).contains(y))
+            .map(|p| (p, 1))
+            .collect_vec()
+    }, false);
+    let (start, end) = (grid.id(&(0, 0)).unwrap(), grid.id(&(2, 2)).unwrap());
+    let best = grid.longest_path(start, end, |c| *c).unwrap();
+    assert_eq!(best.length, 8);
+    assert_eq!(best.path.len(), 9);
+    assert!(best.path.iter().all_unique());
+    assert_eq!(grid.par_longest_path(start, end, |c| *c).unwrap().length, 8);
+
+    let mut chain = Graph::directed();
+    chain.add_edge('a', 'b', 3);
+    chain.add_edge('b', 'c', 4);
+    chain.add_edge('a', 'c', 10);
+    assert_eq!(chain.longest_path(0, 2, |c| *c), Some(LongestPath { length: 10, path: vec![0, 2] }));
+    assert_eq!(chain.par_longest_path(2, 0, |c| *c), None);
+
+    // like day 23 the start only has one way out, the split has to look past it
+    let mut forced = Graph::directed();
+    forced.add_edge("start", "a", 1);
+    for mid in ["b", "c", "d"] {
+        forced.add_edge("a", mid, 1);
+        forced.add_edge(mid, "end", 1);
+    }
+    let search = forced.longest_path_search(forced.id(&"end").unwrap(), |c| *c);
+    let branches = search.branches(forced.id(&"start").unwrap(), 2);
+    assert_eq!(branches.len(), 3);
+    assert!(branches.iter().all(|b| b.path.len() == 3));
+    assert_eq!(forced.par_longest_path(0, forced.id(&"end").unwrap(), |c| *c).unwrap().length, 3);
+}
+
+#[test]
+fn test_all_pairs() {
+    let mut graph = Graph::directed();
+    graph.add_edge('a', 'b', 0);
+    graph.add_edge('b', 'c', -2);
+    graph.add_edge('a', 'c', 1);
+    graph.add_edge('c', 'd', 3);
+    graph.add_edge('d', 'a', 4);
+    graph.add_node('e');
+
+    let fw = graph.floyd_warshall(|w| *w).unwrap();
+    assert_eq!(fw.distance(0, 1), Some(0));
+    assert_eq!(fw.distance(0, 2), Some(-2));
+    assert_eq!(fw.distance(3, 2), Some(2));
+    assert_eq!(fw.distance(0, 4), None);
+    assert_eq!(fw.path(3, 2), Some(vec![3, 0, 1, 2]));
+    assert_eq!(fw.path(4, 4), Some(vec![4]));
+    assert_eq!(graph.johnson(|w| *w), Ok(fw.clone()));
+
+    let (matrix, _) = create_adjacency_matrix(&['a', 'b'], |&n| if n == 'a' { vec![('b', 0)] } else { vec![] });
+    assert_eq!(matrix, [[None, Some(0)], [None, None]]);
+
+    graph.add_edge('c', 'a', 1);
+    let cycle = graph.johnson(|w| *w).unwrap_err();
+    assert_eq!(graph.floyd_warshall(|w| *w), Err(cycle.clone()));
+    let mut nodes = cycle.0.clone();
+    nodes.sort();
+    assert_eq!(nodes, [0, 1, 2]);
+
+    // a small negative cycle that everything else hangs off, finishing the triple loop here
+    // would push the distances past i8::MIN
+    let mut graph: Graph<usize, i8> = Graph::directed();
+    graph.add_edge(0, 1, -1);
+    graph.add_edge(1, 0, 0);
+    for n in 2..12 {
+        graph.add_edge(0, n, 0);
+        graph.add_edge(n, 0, 0);
+    }
+    let mut cycle = graph.floyd_warshall(|w| *w).unwrap_err().0;
+    cycle.sort();
+    assert_eq!(cycle, [0, 1]);
+}
+
+#[test]
+fn test_dot() {
+    let mut graph = Graph::undirected();
+    graph.add_edge("a", "b", 2);
+    graph.add_edge("b", "c\"", 3);
+    let dot = graph.dot()
+        .labels(|_, n| n.to_string())
+        .shapes(|id, _| if id == 0 { "box" } else { "ellipse" }.to_owned())
+        .weights(|w| w.to_string())
+        .highlight_nodes([2], "red")
+        .highlight_edges([(2, 1)], "blue");
+    assert_eq!(dot.to_string(), [
+        "graph \"G\" {",
+        "  n0 [label=\"a\", shape=box];",
+        "  n1 [label=\"b\", shape=ellipse];",
+        "  n2 [label=\"c\\\"\", shape=ellipse, color=red, style=bold];",
+        "  n0 -- n1 [label=\"2\"];",
+        "  n1 -- n2 [label=\"3\", color=blue, penwidth=3];",
+        "}",
+        "",
+    ].join("\n"));
+}
+
+#[test]
+fn test_dag_helpers() {
+    // root -> a, b; a -> c; b -> c; c -> d; d -> e; e -> d
+    let graph: Graph<char, ()> = Graph::from_adjacency([
+        ('r', vec![('a', ()), ('b', ())]),
+        ('a', vec![('c', ())]),
+        ('b', vec![('c', ())]),
+        ('c', vec![('d', ())]),
+        ('d', vec![('e', ())]),
+        ('e', vec![('d', ())]),
+        ('x', vec![]),
+    ], true);
+    let id = |c| graph.id(&c).unwrap();
+
+    assert_eq!(graph.topological_sort(), Err(vec![id('d'), id('e')]));
+    let mut sccs = graph.strongly_connected_components();
+    sccs.iter_mut().for_each(|c| c.sort());
+    assert_eq!(sccs.len(), 6);
+    assert_eq!(sccs[0], [id('d'), id('e')]);
+
+    let tree = graph.dominators(id('r'));
+    assert_eq!(tree.idom(id('c')), Some(id('r')));
+    assert_eq!(tree.idom(id('e')), Some(id('d')));
+    assert!(tree.dominates(id('c'), id('e')) && !tree.dominates(id('a'), id('c')));
+    assert!(!tree.is_reachable(id('x')));
+    assert_eq!(tree.dominated(id('c')), [id('d'), id('e')]);
+    assert_eq!(graph.cut_off_if_removed(id('r')), [5, 0, 0, 2, 1, 0, 0]);
+
+    let dag: Graph<i32, ()> = Graph::from_adjacency([(3, vec![(1, ())]), (2, vec![(1, ())]), (1, vec![(0, ())])], true);
+    assert_eq!(dag.topological_sort().map(|o| o.into_iter().map(|i| *dag.node(i)).collect_vec()), Ok(vec![3, 2, 1, 0]));
+}
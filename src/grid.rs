@@ -1,5 +1,40 @@
-﻿use std::fmt::{Display, Formatter};
+﻿use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+use colored::{Color, Colorize};
 use num::traits::Euclid;
+use crate::graph::Graph;
+use crate::ranges::{min_max_xy, RangeD};
+use crate::search::bucket_dijkstra;
+use crate::vector::Dir4;
+
+// how a walker may move through a grid: it has to go at least `min_run` cells straight before
+// it may turn or stop, and has to turn after `max_run` cells at the latest
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct WalkRules {
+    pub min_run: usize,
+    pub max_run: usize,
+    pub left: bool,
+    pub right: bool,
+    pub reverse: bool,
+}
+
+impl WalkRules {
+    // may turn left or right, but not go back the way it came
+    pub fn new(min_run: usize, max_run: usize) -> Self {
+        assert!(0 < min_run && min_run <= max_run, "runs must satisfy 0 < min <= max");
+        Self { min_run, max_run, left: true, right: true, reverse: false }
+    }
+
+    fn turns(&self, dir: Dir4) -> impl Iterator<Item = Dir4> {
+        [(self.left, dir.rotate_ccw()), (self.right, dir.rotate_cw()), (self.reverse, dir.opposite())]
+            .into_iter()
+            .filter_map(|(allowed, d)| allowed.then_some(d))
+    }
+}
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Grid<T> {
@@ -63,6 +98,171 @@ impl<T> Grid<T> {
     pub fn map<X, FN: Fn(T) -> X>(self, func: FN) -> Grid<X> {
         self.grid.into_iter().map(|x| x.into_iter().map(|x| func(x))).collect()
     }
+
+    // cells that differ between the two grids, including ones only in the larger grid
+    pub fn diff(&self, other: &Grid<T>) -> Vec<(usize, usize)> where T: PartialEq {
+        let mut changed = vec![];
+        for y in 0..self.h.max(other.h) {
+            for x in 0..self.w.max(other.w) {
+                if self.get(x, y) != other.get(x, y) {
+                    changed.push((x, y));
+                }
+            }
+        }
+        changed
+    }
+
+    pub fn highlighted(&self) -> Highlighted<'_, T> {
+        Highlighted {
+            grid: self,
+            layers: vec![],
+        }
+    }
+
+    // binary PGM (P5), one byte of grey per cell
+    pub fn write_pgm<W: Write, FN: Fn(&T) -> u8>(&self, writer: &mut W, shade: FN) -> std::io::Result<()> {
+        write!(writer, "P5\n{} {}\n255\n", self.w, self.h)?;
+        let bytes = self.grid.iter()
+            .flat_map(|row| row.iter().map(&shade))
+            .collect::<Vec<_>>();
+        writer.write_all(&bytes)
+    }
+
+    // binary PPM (P6), one rgb triple per cell
+    pub fn write_ppm<W: Write, FN: Fn(&T) -> [u8; 3]>(&self, writer: &mut W, color: FN) -> std::io::Result<()> {
+        self.write_ppm_with_path(writer, color, [], [0; 3])
+    }
+
+    pub fn write_ppm_with_path<W, FN, IT>(&self, writer: &mut W, color: FN, path: IT, path_color: [u8; 3]) -> std::io::Result<()>
+        where
+            W: Write,
+            FN: Fn(&T) -> [u8; 3],
+            IT: IntoIterator<Item = (usize, usize)>,
+    {
+        let mut pixels = self.grid.iter()
+            .map(|row| row.iter().map(&color).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        for (x, y) in path {
+            if let Some(pixel) = pixels.get_mut(y).and_then(|row| row.get_mut(x)) {
+                *pixel = path_color;
+            }
+        }
+
+        write!(writer, "P6\n{} {}\n255\n", self.w, self.h)?;
+        writer.write_all(&pixels.into_iter().flatten().flatten().collect::<Vec<_>>())
+    }
+
+    pub fn save_pgm<P: AsRef<Path>, FN: Fn(&T) -> u8>(&self, path: P, shade: FN) -> std::io::Result<()> {
+        save_with(path, |writer| self.write_pgm(writer, shade))
+    }
+
+    pub fn save_ppm<P: AsRef<Path>, FN: Fn(&T) -> [u8; 3]>(&self, path: P, color: FN) -> std::io::Result<()> {
+        save_with(path, |writer| self.write_ppm(writer, color))
+    }
+
+    pub fn save_ppm_with_path<P, FN, IT>(&self, path: P, color: FN, route: IT, route_color: [u8; 3]) -> std::io::Result<()>
+        where
+            P: AsRef<Path>,
+            FN: Fn(&T) -> [u8; 3],
+            IT: IntoIterator<Item = (usize, usize)>,
+    {
+        save_with(path, |writer| self.write_ppm_with_path(writer, color, route, route_color))
+    }
+
+    // neighbouring cell in `dir`, None if that's outside the grid
+    pub fn step(&self, (x, y): (usize, usize), dir: Dir4) -> Option<(usize, usize)> {
+        let (dx, dy) = dir.unit::<i64>().into();
+        let (x, y) = (x as i64 + dx, y as i64 + dy);
+        self.get_i(x, y).map(|_| (x as usize, y as usize))
+    }
+
+    // junctions are the walkable cells that don't have exactly two walkable neighbours, every
+    // corridor between two of them becomes a single edge weighted by its length in steps.
+    // `can_enter(tile, dir)` says whether a tile may be entered moving in `dir`, an edge is only
+    // added for the directions a corridor can be walked in, so the graph is always directed
+    pub fn contract_corridors<FW, FE>(&self, is_walkable: FW, can_enter: FE) -> Graph<(usize, usize), usize>
+        where
+            FW: Fn(&T) -> bool,
+            FE: Fn(&T, Dir4) -> bool,
+    {
+        let walkable = |pos: (usize, usize)| self.get(pos.0, pos.1).is_some_and(&is_walkable);
+        let exits = |pos: (usize, usize)| Dir4::ALL.into_iter()
+            .filter_map(move |d| Some((self.step(pos, d)?, d)))
+            .filter(move |(p, _)| walkable(*p));
+        let enter = |(p, d): ((usize, usize), Dir4)| can_enter(self.get(p.0, p.1).unwrap(), d);
+
+        let junctions = self.positions(&is_walkable).into_iter()
+            .filter(|p| exits(*p).count() != 2)
+            .collect::<Vec<_>>();
+        let is_junction = junctions.iter().copied().collect::<HashSet<_>>();
+
+        let mut graph = Graph::directed();
+        for &junction in junctions.iter() {
+            graph.add_node(junction);
+        }
+        for &junction in junctions.iter() {
+            'corridor: for (mut pos, mut dir) in exits(junction) {
+                if !enter((pos, dir)) { continue; }
+                let mut length = 1;
+                while !is_junction.contains(&pos) {
+                    // a corridor cell has exactly one way on that isn't back
+                    let next = exits(pos).find(|(_, d)| *d != dir.opposite()).unwrap();
+                    if !enter(next) { continue 'corridor; }
+                    (pos, dir) = next;
+                    length += 1;
+                }
+                if pos != junction {
+                    graph.add_edge(junction, pos, length);
+                }
+            }
+        }
+        graph
+    }
+
+    // cheapest walk from `start` to `end` under `rules`, paying `cost` for every cell entered.
+    // the first step may go any way. returns every cell visited, including `start`
+    pub fn constrained_walk<FC>(&self, start: (usize, usize), end: (usize, usize), rules: WalkRules, cost: FC) -> Option<(Vec<(usize, usize)>, usize)>
+        where
+            FC: Fn(&T) -> usize,
+    {
+        // position, heading and how many cells it's been going that way
+        type State = ((usize, usize), Option<Dir4>, usize);
+
+        let successors = |&(pos, dir, run): &State| {
+            let moves: Vec<(Dir4, usize)> = match dir {
+                None => Dir4::ALL.into_iter().map(|d| (d, 1)).collect(),
+                Some(dir) => {
+                    let forward = (run < rules.max_run).then_some((dir, run + 1));
+                    let turns = rules.turns(dir).filter(|_| run >= rules.min_run).map(|d| (d, 1));
+                    forward.into_iter().chain(turns).collect()
+                }
+            };
+            moves.into_iter()
+                .filter_map(|(d, run)| {
+                    let next = self.step(pos, d)?;
+                    Some(((next, Some(d), run), cost(self.get(next.0, next.1).unwrap())))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let (states, total) = bucket_dijkstra(
+            &(start, None, 0),
+            successors,
+            |&(pos, dir, run)| pos == end && (dir.is_none() || run >= rules.min_run),
+        )?;
+        Some((states.into_iter().map(|(pos, _, _)| pos).collect(), total))
+    }
+}
+
+// opens `path`, hands the buffered writer to `write` and flushes it
+pub(crate) fn save_with<P, FN>(path: P, write: FN) -> std::io::Result<()>
+    where
+        P: AsRef<Path>,
+        FN: FnOnce(&mut BufWriter<File>) -> std::io::Result<()>,
+{
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(&mut writer)?;
+    writer.flush()
 }
 
 pub struct GridIter<'a, T> {
@@ -116,3 +316,486 @@ impl<T: Display> Display for Grid<T> {
     }
 }
 
+type Layer<'a, T> = (Box<dyn Fn((usize, usize), &T) -> bool + 'a>, Color);
+
+// Display wrapper that colours cells, the first matching layer decides the colour
+pub struct Highlighted<'a, T> {
+    grid: &'a Grid<T>,
+    layers: Vec<Layer<'a, T>>,
+}
+
+impl<'a, T> Highlighted<'a, T> {
+    pub fn cells<IT: IntoIterator<Item = (usize, usize)>>(mut self, cells: IT, color: Color) -> Self {
+        let cells = cells.into_iter().collect::<HashSet<_>>();
+        self.layers.push((Box::new(move |pos, _| cells.contains(&pos)), color));
+        self
+    }
+
+    pub fn predicate<FN: Fn((usize, usize), &T) -> bool + 'a>(mut self, predicate: FN, color: Color) -> Self {
+        self.layers.push((Box::new(predicate), color));
+        self
+    }
+}
+
+impl<'a, T: Display> Display for Highlighted<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.grid.grid.iter().enumerate() {
+            for (x, col) in row.iter().enumerate() {
+                match self.layers.iter().find(|(layer, _)| layer((x, y), col)) {
+                    Some((_, color)) => write!(f, "{}", col.to_string().color(*color).bold())?,
+                    None => write!(f, "{}", col)?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<(i64, i64), T>,
+    pub x_range: Range<i64>,
+    pub y_range: Range<i64>,
+    // sizes of the ranges above, kept as fields to match Grid
+    pub w: usize,
+    pub h: usize,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+            x_range: 0..0,
+            y_range: 0..0,
+            w: 0,
+            h: 0,
+        }
+    }
+
+    fn set_bounds(&mut self, x_range: Range<i64>, y_range: Range<i64>) {
+        self.w = (x_range.end - x_range.start) as usize;
+        self.h = (y_range.end - y_range.start) as usize;
+        self.x_range = x_range;
+        self.y_range = y_range;
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    #[inline]
+    pub fn get(&self, x: i64, y: i64) -> Option<&T> {
+        self.cells.get(&(x, y))
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, x: i64, y: i64) -> Option<&mut T> {
+        self.cells.get_mut(&(x, y))
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        self.cells.contains_key(&(x, y))
+    }
+
+    pub fn insert(&mut self, x: i64, y: i64, value: T) -> Option<T> {
+        if self.cells.is_empty() {
+            self.set_bounds(x..x + 1, y..y + 1);
+        } else {
+            self.set_bounds(
+                self.x_range.start.min(x)..self.x_range.end.max(x + 1),
+                self.y_range.start.min(y)..self.y_range.end.max(y + 1),
+            );
+        }
+        self.cells.insert((x, y), value)
+    }
+
+    // bounds only ever grow on insert, shrink them back down after removing
+    pub fn remove(&mut self, x: i64, y: i64) -> Option<T> {
+        let removed = self.cells.remove(&(x, y));
+        if removed.is_some() && (self.x_range.start == x || self.x_range.end == x + 1
+            || self.y_range.start == y || self.y_range.end == y + 1) {
+            self.recalculate_bounds();
+        }
+        removed
+    }
+
+    pub fn get_or_insert_with<FN: FnOnce() -> T>(&mut self, x: i64, y: i64, default: FN) -> &mut T {
+        if !self.cells.contains_key(&(x, y)) {
+            self.insert(x, y, default());
+        }
+        self.cells.get_mut(&(x, y)).unwrap()
+    }
+
+    fn recalculate_bounds(&mut self) {
+        let (x_range, y_range) = min_max_xy(self.cells.keys().cloned())
+            .unwrap_or((0..0, 0..0));
+        self.set_bounds(x_range, y_range);
+    }
+
+    // row-major like Grid, the cells themselves are stored in no particular order
+    pub fn positions<FN: Fn(&T) -> bool>(&self, predicate: FN) -> Vec<(i64, i64)> {
+        self.iter()
+            .filter(|(_, item)| predicate(item))
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((i64, i64), &T)> {
+        let mut cells = self.cells.iter().map(|(pos, item)| (*pos, item)).collect::<Vec<_>>();
+        cells.sort_unstable_by_key(|((x, y), _)| (*y, *x));
+        cells.into_iter()
+    }
+
+    pub fn map<X, FN: Fn(T) -> X>(self, func: FN) -> SparseGrid<X> {
+        SparseGrid {
+            cells: self.cells.into_iter().map(|(pos, x)| (pos, func(x))).collect(),
+            x_range: self.x_range,
+            y_range: self.y_range,
+            w: self.w,
+            h: self.h,
+        }
+    }
+
+    pub fn to_grid(&self, fill: &T) -> Grid<T> where T: Clone {
+        self.y_range.clone()
+            .map(|y| self.x_range.clone()
+                .map(move |x| self.get(x, y).unwrap_or(fill).clone()))
+            .collect()
+    }
+
+    pub fn display_with<'a>(&'a self, fill: &'a T) -> SparseGridDisplay<'a, T> {
+        SparseGridDisplay {
+            grid: self,
+            fill,
+        }
+    }
+}
+
+impl<T> FromIterator<((i64, i64), T)> for SparseGrid<T> {
+    fn from_iter<TIT: IntoIterator<Item=((i64, i64), T)>>(iter: TIT) -> Self {
+        let mut grid = Self {
+            cells: iter.into_iter().collect(),
+            ..Self::new()
+        };
+        grid.recalculate_bounds();
+        grid
+    }
+}
+
+impl<T> From<Grid<T>> for SparseGrid<T> {
+    fn from(grid: Grid<T>) -> Self {
+        grid.grid.into_iter().enumerate()
+            .flat_map(|(y, row)| row.into_iter().enumerate()
+                .map(move |(x, item)| ((x as i64, y as i64), item)))
+            .collect()
+    }
+}
+
+pub struct SparseGridDisplay<'a, T> {
+    grid: &'a SparseGrid<T>,
+    fill: &'a T,
+}
+
+impl<'a, T: Display> Display for SparseGridDisplay<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for y in self.grid.y_range.clone() {
+            for x in self.grid.x_range.clone() {
+                write!(f, "{}", self.grid.get(x, y).unwrap_or(self.fill))?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sparse_grid_bounds() {
+    let mut grid = SparseGrid::new();
+    grid.insert(-2, 3, '#');
+    grid.insert(4, -1, '#');
+    assert_eq!((grid.x_range.clone(), grid.y_range.clone()), (-2..5, -1..4));
+    assert_eq!((grid.w, grid.h), (7, 5));
+    assert_eq!(grid.display_with(&'.').to_string().lines().count(), 5);
+
+    // row-major regardless of insertion order
+    grid.insert(0, 3, 'o');
+    grid.insert(-1, 0, 'o');
+    assert_eq!(grid.iter().map(|(p, _)| p).collect::<Vec<_>>(), [(4, -1), (-1, 0), (-2, 3), (0, 3)]);
+    assert_eq!(grid.positions(|c| *c == 'o'), [(-1, 0), (0, 3)]);
+    grid.remove(0, 3);
+    grid.remove(-1, 0);
+
+    grid.remove(4, -1);
+    assert_eq!((grid.x_range.clone(), grid.y_range.clone()), (-2..-1, 3..4));
+    assert_eq!((grid.w, grid.h), (1, 1));
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct GridN<T, const D: usize> {
+    cells: Vec<T>,
+    pub dims: [usize; D],
+}
+
+impl<T, const D: usize> GridN<T, D> {
+    pub fn new(dims: [usize; D], fill: T) -> Self where T: Clone {
+        Self {
+            cells: vec![fill; dims.iter().product()],
+            dims,
+        }
+    }
+
+    pub fn from_fn<FN: FnMut([usize; D]) -> T>(dims: [usize; D], func: FN) -> Self {
+        Self {
+            cells: RangeD::from_range_1d(dims.map(|d| 0..d)).iter().map(func).collect(),
+            dims,
+        }
+    }
+
+    #[inline]
+    fn index(&self, pos: [usize; D]) -> Option<usize> {
+        let mut index = 0;
+        for (p, d) in pos.into_iter().zip(self.dims) {
+            if p >= d { return None; }
+            index = index * d + p;
+        }
+        Some(index)
+    }
+
+    #[inline]
+    pub fn get(&self, pos: [usize; D]) -> Option<&T> {
+        self.index(pos).map(|i| &self.cells[i])
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, pos: [usize; D]) -> Option<&mut T> {
+        self.index(pos).map(|i| &mut self.cells[i])
+    }
+
+    pub fn bounds(&self) -> RangeD<usize, D> {
+        RangeD::from_range_1d(self.dims.map(|d| 0..d))
+    }
+
+    pub fn contains_range(&self, range: &RangeD<usize, D>) -> bool {
+        (0..D).all(|i| range.end[i] <= self.dims[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ([usize; D], &T)> {
+        self.bounds().into_iter().zip(self.cells.iter())
+    }
+
+    pub fn iter_range<'a>(&'a self, range: &'a RangeD<usize, D>) -> impl Iterator<Item = ([usize; D], &'a T)> {
+        range.iter().filter_map(|p| self.get(p).map(|x| (p, x)))
+    }
+
+    pub fn fill_range(&mut self, range: &RangeD<usize, D>, value: T) where T: Clone {
+        for p in range {
+            if let Some(x) = self.get_mut(p) {
+                *x = value.clone();
+            }
+        }
+    }
+
+    // cells outside the grid are never free
+    pub fn is_range_free<FN: Fn(&T) -> bool>(&self, range: &RangeD<usize, D>, is_free: FN) -> bool {
+        self.contains_range(range) && range.iter().all(|p| is_free(&self.cells[self.index(p).unwrap()]))
+    }
+
+    pub fn map<X, FN: Fn(T) -> X>(self, func: FN) -> GridN<X, D> {
+        GridN {
+            cells: self.cells.into_iter().map(func).collect(),
+            dims: self.dims,
+        }
+    }
+
+    // the plane through `at` spanned by the two axes, `at` picks the cell on every other axis
+    pub fn slice(&self, col_axis: usize, row_axis: usize, at: [usize; D]) -> GridNSlice<'_, T, D> {
+        assert!(col_axis < D && row_axis < D, "slice axes {col_axis} and {row_axis} must be below {D}");
+        assert_ne!(col_axis, row_axis, "slice needs two different axes");
+        assert!(self.index(at).is_some(), "slice point {at:?} is outside the grid {:?}", self.dims);
+        GridNSlice {
+            grid: self,
+            col_axis,
+            row_axis,
+            at,
+            rows_descending: false,
+        }
+    }
+}
+
+pub struct GridNSlice<'a, T, const D: usize> {
+    grid: &'a GridN<T, D>,
+    col_axis: usize,
+    row_axis: usize,
+    at: [usize; D],
+    rows_descending: bool,
+}
+
+impl<'a, T, const D: usize> GridNSlice<'a, T, D> {
+    // e.g. to print z upwards
+    pub fn rows_descending(self) -> Self {
+        Self {
+            rows_descending: true,
+            ..self
+        }
+    }
+
+    pub fn render<FN: Fn(&T) -> String>(&self, cell: FN) -> String {
+        let rows = 0..self.grid.dims[self.row_axis];
+        let rows: Box<dyn Iterator<Item=usize>> = if self.rows_descending {
+            Box::new(rows.rev())
+        } else {
+            Box::new(rows)
+        };
+
+        let mut out = String::new();
+        let mut pos = self.at;
+        for r in rows {
+            pos[self.row_axis] = r;
+            for c in 0..self.grid.dims[self.col_axis] {
+                pos[self.col_axis] = c;
+                out.push_str(&cell(self.grid.get(pos).unwrap()));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl<'a, T: Display, const D: usize> Display for GridNSlice<'a, T, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(|x| x.to_string()))
+    }
+}
+
+#[test]
+fn test_contract_corridors() {
+    let grid: Grid<char> = ["#.####", "#....#", "#.##.#", "#.>..#", "####.#"].iter()
+        .map(|row| row.chars())
+        .collect();
+    let graph = grid.contract_corridors(|c| *c != '#', |c, d| *c == '.' || Dir4::try_from(*c) == Ok(d));
+    let id = |pos| graph.id(&pos).unwrap();
+
+    assert_eq!(graph.nodes(), &[(1, 0), (1, 1), (4, 3), (4, 4)]);
+    assert_eq!(graph.edge_count(), 7);
+    let mut from_junction = graph.neighbours(id((1, 1))).to_vec();
+    from_junction.sort();
+    assert_eq!(from_junction, [(id((1, 0)), 1), (id((4, 3)), 5), (id((4, 3)), 5)]);
+    // the slope only lets the southern corridor be walked one way
+    assert_eq!(graph.neighbours(id((4, 3))).iter().filter(|(to, _)| *to == id((1, 1))).count(), 1);
+}
+
+#[test]
+fn test_constrained_walk() {
+    let grid: Grid<usize> = ["111111111111", "999999999991", "999999999991", "999999999991", "999999999991"].iter()
+        .map(|row| row.chars().map(|c| c.to_digit(10).unwrap() as usize))
+        .collect();
+    let end = (grid.w - 1, grid.h - 1);
+
+    let (path, cost) = grid.constrained_walk((0, 0), end, WalkRules::new(4, 10), |c| *c).unwrap();
+    assert_eq!(cost, 71);
+    assert_eq!((path.first(), path.last()), (Some(&(0, 0)), Some(&end)));
+    assert_eq!(grid.constrained_walk((0, 0), end, WalkRules::new(1, 3), |c| *c).unwrap().1, 59);
+
+    // stepping one cell at a time with only reversing allowed just bounces back and forth
+    let bounce = WalkRules { left: false, right: false, reverse: true, ..WalkRules::new(1, 1) };
+    assert_eq!(grid.constrained_walk((0, 0), (2, 0), bounce, |c| *c), None);
+    assert_eq!(grid.constrained_walk((0, 0), (1, 0), bounce, |c| *c).map(|(p, c)| (p.len(), c)), Some((2, 1)));
+}
+
+#[test]
+fn test_diff_and_highlight() {
+    let before: Grid<char> = ["O.#", "..O"].iter().map(|row| row.chars()).collect();
+    let after: Grid<char> = ["..#", "O.O", "..."].iter().map(|row| row.chars()).collect();
+    assert_eq!(before.diff(&after), [(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)]);
+    assert_eq!(before.diff(&before), []);
+
+    // the first matching layer wins and everything else is printed as is
+    let highlighted = || before.highlighted()
+        .cells([(0, 0)], Color::Red)
+        .predicate(|_, c| *c == 'O', Color::Green);
+    colored::control::set_override(true);
+    let coloured = highlighted().to_string();
+    let red = "O".color(Color::Red).bold().to_string();
+    let green = "O".color(Color::Green).bold().to_string();
+    colored::control::set_override(false);
+    let plain = highlighted().to_string();
+    colored::control::unset_override();
+
+    assert_eq!(plain, "O.#\n..O\n");
+    assert_ne!(red, green);
+    assert_eq!(coloured, format!("{red}.#\n..{green}\n"));
+}
+
+#[test]
+fn test_grid_n_ranges() {
+    let mut grid = GridN::new([3, 2, 4], '.');
+    let block = RangeD::from_range_1d([0..2, 1..2, 1..3]);
+    grid.fill_range(&block, '#');
+
+    assert_eq!(grid.iter().filter(|(_, c)| **c == '#').count(), 4);
+    assert!(grid.iter_range(&block).all(|(_, c)| *c == '#'));
+    // positions past the edge are skipped rather than yielded
+    assert_eq!(grid.iter_range(&RangeD::from_range_1d([2..5, 0..1, 0..1])).count(), 1);
+
+    let free = |c: &char| *c == '.';
+    assert!(!grid.is_range_free(&block, free));
+    assert!(grid.is_range_free(&RangeD::from_range_1d([2..3, 0..2, 0..4]), free));
+    assert!(!grid.is_range_free(&RangeD::from_range_1d([2..4, 0..1, 0..1]), free));
+
+    // x across, z up, looking at y = 1
+    assert_eq!(grid.slice(0, 2, [0, 1, 0]).rows_descending().to_string(), "...\n##.\n##.\n...\n");
+    assert_eq!(grid.slice(1, 2, [2, 0, 0]).render(|c| c.to_string()), "..\n..\n..\n..\n");
+}
+
+#[test]
+#[should_panic(expected = "two different axes")]
+fn test_grid_n_slice_same_axis() {
+    GridN::new([2, 2], 0).slice(1, 1, [0, 0]);
+}
+
+#[test]
+#[should_panic(expected = "outside the grid")]
+fn test_grid_n_slice_outside() {
+    GridN::new([2, 2, 2], 0).slice(0, 1, [0, 0, 2]);
+}
+
+#[test]
+fn test_image_output() {
+    let grid: Grid<usize> = ["131", "911"].iter()
+        .map(|row| row.chars().map(|c| c.to_digit(10).unwrap() as usize))
+        .collect();
+
+    let mut pgm = vec![];
+    grid.write_pgm(&mut pgm, |c| *c as u8 * 10).unwrap();
+    assert_eq!(pgm, [b"P5\n3 2\n255\n".as_slice(), &[10, 30, 10, 90, 10, 10]].concat());
+
+    // a route drawn over the cost map, points off the grid are skipped
+    let route = [(0, 0), (1, 0), (2, 0), (2, 1), (3, 0), (0, 5)];
+    let mut ppm = vec![];
+    grid.write_ppm_with_path(&mut ppm, |c| [*c as u8; 3], route, [255, 0, 0]).unwrap();
+    let header = b"P6\n3 2\n255\n";
+    assert_eq!(&ppm[..header.len()], header);
+    assert_eq!(ppm[header.len()..], [
+        255, 0, 0, 255, 0, 0, 255, 0, 0,
+        9, 9, 9, 1, 1, 1, 255, 0, 0,
+    ]);
+
+    let mut plain = vec![];
+    grid.write_ppm(&mut plain, |c| [*c as u8; 3]).unwrap();
+    assert_eq!(plain[header.len()..], [1, 1, 1, 3, 3, 3, 1, 1, 1, 9, 9, 9, 1, 1, 1, 1, 1, 1]);
+}
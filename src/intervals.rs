@@ -0,0 +1,295 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Neg, Range, Sub};
+use itertools::Itertools;
+use num::Zero;
+
+// disjoint, non-touching, non-empty ranges kept sorted by start
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self { ranges: vec![] }
+    }
+}
+
+impl<T> IntervalSet<T>
+    where
+        T: Copy + Ord + Add<Output=T> + Sub<Output=T> + Zero,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_range(range: Range<T>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Range<T>> {
+        self.ranges.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    // total number of values covered
+    pub fn len(&self) -> T {
+        self.ranges.iter().fold(T::zero(), |l, r| l + (r.end - r.start))
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last().map(|r| r.end)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= *value);
+        self.ranges.get(i).is_some_and(|r| r.contains(value))
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() { return; }
+        // everything in i..j touches or overlaps the new range
+        let i = self.ranges.partition_point(|r| r.end < range.start);
+        let j = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = if i < j {
+            range.start.min(self.ranges[i].start)..range.end.max(self.ranges[j - 1].end)
+        } else {
+            range
+        };
+        self.ranges.splice(i..j, [merged]);
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        if range.is_empty() { return; }
+        let i = self.ranges.partition_point(|r| r.end <= range.start);
+        let j = self.ranges.partition_point(|r| r.start < range.end);
+        if i >= j { return; }
+
+        let mut kept = vec![];
+        if self.ranges[i].start < range.start {
+            kept.push(self.ranges[i].start..range.start);
+        }
+        if range.end < self.ranges[j - 1].end {
+            kept.push(range.end..self.ranges[j - 1].end);
+        }
+        self.ranges.splice(i..j, kept);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut out = self.clone();
+        for r in other.ranges.iter() {
+            out.insert(r.clone());
+        }
+        out
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+            if a.end < b.end { i += 1; } else { j += 1; }
+        }
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut out = self.clone();
+        for r in other.ranges.iter() {
+            out.remove(r.clone());
+        }
+        out
+    }
+
+    // everything within `bounds` that isn't in the set
+    pub fn complement(&self, bounds: Range<T>) -> Self {
+        let mut out = Self::new();
+        out.insert(bounds);
+        out.difference(self)
+    }
+
+    pub fn shift(&self, offset: T) -> Self {
+        Self {
+            ranges: self.ranges.iter().map(|r| r.start + offset..r.end + offset).collect(),
+        }
+    }
+}
+
+impl<T> FromIterator<Range<T>> for IntervalSet<T>
+    where
+        T: Copy + Ord + Add<Output=T> + Sub<Output=T> + Zero,
+{
+    fn from_iter<IT: IntoIterator<Item=Range<T>>>(iter: IT) -> Self {
+        let mut out = Self::new();
+        for r in iter {
+            out.insert(r);
+        }
+        out
+    }
+}
+
+impl<T: Display> Display for IntervalSet<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{{}}}", self.ranges.iter().map(|r| format!("{}..{}", r.start, r.end)).join(", "))
+    }
+}
+
+// shifts every value inside a piece by that piece's offset and leaves everything else alone.
+// pieces are disjoint, non-empty, sorted by start and never have a zero offset
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PiecewiseShift<T> {
+    pieces: Vec<(Range<T>, T)>,
+}
+
+impl<T> Default for PiecewiseShift<T> {
+    fn default() -> Self {
+        Self { pieces: vec![] }
+    }
+}
+
+impl<T> PiecewiseShift<T>
+    where
+        T: Copy + Ord + Add<Output=T> + Sub<Output=T> + Zero,
+{
+    pub fn identity() -> Self {
+        Self::default()
+    }
+
+    // where pieces overlap the earlier one wins, same as looking them up with `find`
+    pub fn new<IT: IntoIterator<Item=(Range<T>, T)>>(pieces: IT) -> Self {
+        let mut covered = IntervalSet::new();
+        let mut out = vec![];
+        for (range, offset) in pieces {
+            let uncovered = IntervalSet::from_range(range.clone()).difference(&covered);
+            out.extend(uncovered.ranges.into_iter().map(|r| (r, offset)));
+            covered.insert(range);
+        }
+        Self::from_disjoint(out)
+    }
+
+    fn from_disjoint(mut pieces: Vec<(Range<T>, T)>) -> Self {
+        pieces.retain(|(r, offset)| !r.is_empty() && !offset.is_zero());
+        pieces.sort_by_key(|(r, _)| r.start);
+
+        let mut merged: Vec<(Range<T>, T)> = Vec::with_capacity(pieces.len());
+        for (r, offset) in pieces {
+            match merged.last_mut() {
+                Some((last, o)) if last.end == r.start && *o == offset => last.end = r.end,
+                _ => merged.push((r, offset)),
+            }
+        }
+        Self { pieces: merged }
+    }
+
+    pub fn pieces(&self) -> &[(Range<T>, T)] {
+        &self.pieces
+    }
+
+    pub fn offset_at(&self, value: T) -> T {
+        let i = self.pieces.partition_point(|(r, _)| r.end <= value);
+        match self.pieces.get(i) {
+            Some((r, offset)) if r.contains(&value) => *offset,
+            _ => T::zero(),
+        }
+    }
+
+    pub fn apply(&self, value: T) -> T {
+        value + self.offset_at(value)
+    }
+
+    // calls `func` with every maximal subrange of `range` and the offset applied to it
+    fn split<FN: FnMut(Range<T>, T)>(&self, range: Range<T>, mut func: FN) {
+        let i = self.pieces.partition_point(|(r, _)| r.end <= range.start);
+        let mut cursor = range.start;
+        for (r, offset) in self.pieces[i..].iter().take_while(|(r, _)| r.start < range.end) {
+            let start = r.start.max(cursor);
+            let end = r.end.min(range.end);
+            if cursor < start { func(cursor..start, T::zero()); }
+            func(start..end, *offset);
+            cursor = end;
+        }
+        if cursor < range.end { func(cursor..range.end, T::zero()); }
+    }
+
+    pub fn apply_set(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut out = IntervalSet::new();
+        for range in set.iter() {
+            self.split(range.clone(), |r, offset| out.insert(r.start + offset..r.end + offset));
+        }
+        out
+    }
+
+    // a single map doing `self` and then `then`
+    pub fn compose(&self, then: &Self) -> Self {
+        let mut out = vec![];
+        for (r, offset) in self.pieces.iter() {
+            then.split(r.start + *offset..r.end + *offset, |image, o| {
+                out.push((image.start - *offset..image.end - *offset, *offset + o));
+            });
+        }
+
+        // outside of our pieces only `then` does anything
+        let domain: IntervalSet<T> = self.pieces.iter().map(|(r, _)| r.clone()).collect();
+        for (r, offset) in then.pieces.iter() {
+            let rest = IntervalSet::from_range(r.clone()).difference(&domain);
+            out.extend(rest.ranges.into_iter().map(|r| (r, *offset)));
+        }
+        Self::from_disjoint(out)
+    }
+
+    // only a true inverse if the map is a bijection (every piece's image is covered by pieces),
+    // otherwise values hit twice go back to whichever piece comes first
+    pub fn invert(&self) -> Self where T: Neg<Output=T> {
+        Self::new(self.pieces.iter().map(|(r, offset)| (r.start + *offset..r.end + *offset, -*offset)))
+    }
+}
+
+#[test]
+fn test_interval_set() {
+    let a: IntervalSet<i64> = [0..5, 10..15, 4..7, 15..16].into_iter().collect();
+    assert_eq!(a.ranges(), &[0..7, 10..16]);
+    assert_eq!(a.len(), 13);
+
+    let b: IntervalSet<i64> = [3..11, 14..20].into_iter().collect();
+    assert_eq!(a.union(&b), IntervalSet::from_range(0..20));
+    assert_eq!(a.intersection(&b).ranges(), &[3..7, 10..11, 14..16]);
+    assert_eq!(a.difference(&b).ranges(), &[0..3, 11..14]);
+    assert_eq!(a.complement(-5..12).ranges(), &[-5..0, 7..10]);
+    assert_eq!(a.shift(-2).ranges(), &[-2..5, 8..14]);
+    assert!(a.contains(&6) && !a.contains(&7) && a.contains(&10));
+}
+
+#[test]
+fn test_piecewise_shift() {
+    let a = PiecewiseShift::new([(98..100, -48), (50..98, 2)]);
+    let b = PiecewiseShift::new([(15..52, -15), (52..54, -15), (0..15, 39)]);
+    assert_eq!(a.pieces(), &[(50..98, 2), (98..100, -48)]);
+    assert_eq!(b.pieces(), &[(0..15, 39), (15..54, -15)]);
+
+    let ab = a.compose(&b);
+    for x in -5..110 {
+        assert_eq!(ab.apply(x), b.apply(a.apply(x)));
+        assert_eq!(a.invert().apply(a.apply(x)), x);
+    }
+
+    let seeds: IntervalSet<i64> = [79..93, 55..68].into_iter().collect();
+    let expected: IntervalSet<i64> = seeds.iter().flat_map(|r| r.clone()).map(|x| ab.apply(x)).map(|x| x..x + 1).collect();
+    assert_eq!(ab.apply_set(&seeds), expected);
+}
@@ -61,6 +61,11 @@ mod day25;
 mod graph;
 mod day13_22;
 mod backtracking;
+mod cycle;
+mod counting;
+mod intervals;
+mod spatial;
+mod search;
 
 fn main() -> std::io::Result<()> {
     backtracking::sudoku::test();
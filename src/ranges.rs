@@ -2,7 +2,7 @@
 use std::fmt::{Display, Formatter};
 use std::ops::{Add, Range};
 use itertools::Itertools;
-use num::{Bounded, One};
+use num::{Bounded, One, PrimInt};
 
 pub(crate) fn intersect(a: &Range<usize>, b: &Range<usize>) -> Option<Range<usize>> {
     if !intersects(a, b) { return None; }
@@ -13,21 +13,6 @@ pub(crate) fn intersects(a: &Range<usize>, b: &Range<usize>) -> bool {
     a.contains(&b.start) || b.contains(&a.start)
 }
 
-pub(crate) fn intersect_n<const N: usize>(a: &[Range<i64>; N], b: &[Range<i64>; N]) -> Option<[Range<i64>; N]> {
-    let mut out = a.clone();
-    for (a, b) in out.iter_mut().zip(b.iter()) {
-        *a = a.start.max(b.start)..a.end.min(b.end);
-    }
-    
-    // dbg!(&out);
-    
-    if out.iter().all(|r| {
-        r.end > r.start
-    }) {
-        Some(out)
-    } else { None }
-}
-
 pub(crate) fn min_max_xy<Iter, Idx>(iter: Iter) -> Option<(Range<Idx>, Range<Idx>)>
 where
     Iter: Iterator<Item=(Idx, Idx)>,
@@ -69,21 +54,30 @@ pub(crate) fn min_max_comp<Iter, Idx, const N: usize>(iter: Iter) -> Option<([Id
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub struct RangeD<const N: usize> {
-    pub(crate) start: [usize; N],
-    pub(crate) end: [usize; N]
+pub struct RangeD<T, const N: usize> {
+    pub(crate) start: [T; N],
+    pub(crate) end: [T; N]
 }
 
-impl<'a, const N: usize> IntoIterator for &'a RangeD<N> {
-    type Item = [usize; N];
-    type IntoIter = RangeDIterator<'a, N>;
+impl<T: PrimInt, const N: usize> IntoIterator for &RangeD<T, N> {
+    type Item = [T; N];
+    type IntoIter = RangeDIterator<T, N>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-impl<const N: usize> Display for RangeD<N> {
+impl<T: PrimInt, const N: usize> IntoIterator for RangeD<T, N> {
+    type Item = [T; N];
+    type IntoIter = RangeDIterator<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        RangeDIterator::new(&self)
+    }
+}
+
+impl<T: Display, const N: usize> Display for RangeD<T, N> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "({})", self.start.iter().zip(self.end.iter()).map(|(s,e)| {
             format!("{s}..{e}")
@@ -91,43 +85,85 @@ impl<const N: usize> Display for RangeD<N> {
     }
 }
 
-impl<const N: usize> RangeD<N> {
-    pub fn from_range_1d(ranges: [Range<usize>; N]) -> Self {
+impl<T: PrimInt, const N: usize> RangeD<T, N> {
+    pub fn from_range_1d(ranges: [Range<T>; N]) -> Self {
         Self {
             start: ranges.clone().map(|x| x.start),
             end: ranges.clone().map(|x| x.end),
         }
     }
+
+    pub fn to_ranges(&self) -> [Range<T>; N] {
+        std::array::from_fn(|i| self.start[i]..self.end[i])
+    }
     
-    pub fn offset(&mut self, offset: usize) {
-        self.start.iter_mut().for_each(|i| *i += offset);
-        self.end.iter_mut().for_each(|i| *i += offset);
+    // the in-place versions panic where the checked ones would return None
+    pub fn offset(&mut self, offset: T) {
+        *self = self.checked_offset(offset).expect("offset moves a bound out of range");
     }
     
-    pub fn offset_neg(&mut self, offset: usize) {
-        self.start.iter_mut().for_each(|i| *i -= offset);
-        self.end.iter_mut().for_each(|i| *i -= offset);
+    pub fn offset_neg(&mut self, offset: T) {
+        *self = self.checked_offset_neg(offset).expect("offset moves a bound below the minimum");
     }
     
-    pub fn offset_component(&mut self, i: usize, offset: usize) {
-        self.start[i] += offset;
-        self.end[i] += offset;
+    pub fn offset_component(&mut self, i: usize, offset: T) {
+        *self = self.checked_offset_component(i, offset).expect("offset moves a bound out of range");
+    }
+
+    pub fn offset_component_neg(&mut self, i: usize, offset: T) {
+        *self = self.checked_offset_component_neg(i, offset).expect("offset moves a bound below the minimum");
+    }
+
+    // the checked versions leave self alone and return None if any bound over/underflows
+    pub fn checked_offset(&self, offset: T) -> Option<Self> {
+        self.checked_map(|_, x| x.checked_add(&offset))
+    }
+
+    pub fn checked_offset_neg(&self, offset: T) -> Option<Self> {
+        self.checked_map(|_, x| x.checked_sub(&offset))
+    }
+
+    pub fn checked_offset_component(&self, i: usize, offset: T) -> Option<Self> {
+        self.checked_map(|j, x| if i == j { x.checked_add(&offset) } else { Some(x) })
+    }
+
+    pub fn checked_offset_component_neg(&self, i: usize, offset: T) -> Option<Self> {
+        self.checked_map(|j, x| if i == j { x.checked_sub(&offset) } else { Some(x) })
     }
 
-    pub fn offset_component_neg(&mut self, i: usize, offset: usize) {
-        self.start[i] -= offset;
-        self.end[i] -= offset;
+    fn checked_map<FN: Fn(usize, T) -> Option<T>>(&self, func: FN) -> Option<Self> {
+        let mut out = self.clone();
+        for i in 0..N {
+            out.start[i] = func(i, self.start[i])?;
+            out.end[i] = func(i, self.end[i])?;
+        }
+        Some(out)
     }
     
-    pub fn volume(&self) -> usize {
-        self.start.iter().zip(self.end.iter()).map(|(s, e)| e - s).product()
+    pub fn volume(&self) -> T {
+        self.start.iter().zip(self.end.iter()).map(|(&s, &e)| e - s).fold(T::one(), |v, l| v * l)
+    }
+
+    pub fn volume_checked(&self) -> Option<T> {
+        self.start.iter().zip(self.end.iter()).try_fold(T::one(), |v, (&s, &e)| v.checked_mul(&(e.checked_sub(&s)?)))
+    }
+
+    // extents are taken in i128 so wide signed boxes can't overflow T, and empty axes count as 0
+    pub fn volume_u128(&self) -> u128 {
+        self.start.iter().zip(self.end.iter()).map(|(&s, &e)| {
+            if e <= s { return 0; }
+            match (s.to_i128(), e.to_i128()) {
+                // e > s so the true extent fits in a u128 even when the i128 subtraction wraps
+                (Some(s), Some(e)) => e.wrapping_sub(s) as u128,
+                // only u128 bounds past i128::MAX get here, and those can't underflow
+                _ => (e - s).to_u128().unwrap(),
+            }
+        }).product()
     }
     
     pub fn intersect(&self, other: &Self) -> Option<Self> {
-        // if !self.intersects(other) { return None; }
-        
-        let mut start = [0; N];
-        let mut end = [0; N];
+        let mut start = self.start;
+        let mut end = self.end;
         for i in 0..N {
             start[i] = self.start[i].max(other.start[i]);
             end[i] = self.end[i].min(other.end[i]);
@@ -141,65 +177,163 @@ impl<const N: usize> RangeD<N> {
         } else {
             None
         }
-        
-        
     }
     
     pub fn intersects(&self, other: &Self) -> bool {
-        fn contains_simple<const N: usize>(a: &RangeD<N>, b: &RangeD<N>) -> bool {
-            (0..N).all(|i| {
-                a.start[i] <= b.start[i] && b.start[i] < a.end[i]
-            })
+        (0..N).all(|i| self.start[i].max(other.start[i]) < self.end[i].min(other.end[i]))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..N).any(|i| self.start[i] >= self.end[i])
+    }
+
+    pub fn contains(&self, point: &[T; N]) -> bool {
+        (0..N).all(|i| self.start[i] <= point[i] && point[i] < self.end[i])
+    }
+
+    pub fn contains_range(&self, other: &Self) -> bool {
+        other.is_empty() || (0..N).all(|i| self.start[i] <= other.start[i] && other.end[i] <= self.end[i])
+    }
+
+    // the part of self outside of other, as at most 2N disjoint boxes.
+    // slabs are cut off one axis at a time, so each one spans what's left of the previous axes
+    pub fn difference(&self, other: &Self) -> Vec<Self> {
+        let Some(overlap) = self.intersect(other) else {
+            return if self.is_empty() { vec![] } else { vec![self.clone()] };
+        };
+
+        let mut out = vec![];
+        let mut rest = self.clone();
+        for i in 0..N {
+            if rest.start[i] < overlap.start[i] {
+                let mut below = rest.clone();
+                below.end[i] = overlap.start[i];
+                out.push(below);
+            }
+            if overlap.end[i] < rest.end[i] {
+                let mut above = rest.clone();
+                above.start[i] = overlap.end[i];
+                out.push(above);
+            }
+            rest.start[i] = overlap.start[i];
+            rest.end[i] = overlap.end[i];
         }
-        
-        contains_simple::<N>(self, other) || contains_simple::<N>(other, self)
+        out
     }
     
-    pub fn len_d(&self, i: usize) -> usize {
+    pub fn len_d(&self, i: usize) -> T {
         self.end[i] - self.start[i]
     }
     
-    pub fn iter(&self) -> RangeDIterator<N> {
+    pub fn iter(&self) -> RangeDIterator<T, N> {
         RangeDIterator::new(self)
     }
 }
 
-pub struct RangeDIterator<'a, const N: usize> {
-    ranges: &'a RangeD<N>,
-    values: [usize; N]
+// union of boxes, stored as disjoint boxes so the volume is just a sum
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BoxSet<T, const N: usize> {
+    boxes: Vec<RangeD<T, N>>,
+}
+
+impl<T, const N: usize> Default for BoxSet<T, N> {
+    fn default() -> Self {
+        Self { boxes: vec![] }
+    }
 }
 
-impl<'a, const N: usize> RangeDIterator<'a, N> {
-    pub fn new(range: &'a RangeD<N>) -> Self {
+impl<T: PrimInt, const N: usize> BoxSet<T, N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn boxes(&self) -> &[RangeD<T, N>] {
+        &self.boxes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.boxes.is_empty()
+    }
+
+    pub fn contains(&self, point: &[T; N]) -> bool {
+        self.boxes.iter().any(|b| b.contains(point))
+    }
+
+    pub fn intersects(&self, range: &RangeD<T, N>) -> bool {
+        self.boxes.iter().any(|b| b.intersects(range))
+    }
+
+    // only the parts not already covered get added
+    pub fn insert(&mut self, range: RangeD<T, N>) {
+        let mut pieces = if range.is_empty() { vec![] } else { vec![range] };
+        for b in self.boxes.iter() {
+            if pieces.is_empty() { return; }
+            pieces = pieces.into_iter()
+                .flat_map(|p| if p.intersects(b) { p.difference(b) } else { vec![p] })
+                .collect();
+        }
+        self.boxes.extend(pieces);
+    }
+
+    pub fn remove(&mut self, range: &RangeD<T, N>) {
+        self.boxes = std::mem::take(&mut self.boxes).into_iter()
+            .flat_map(|b| if b.intersects(range) { b.difference(range) } else { vec![b] })
+            .collect();
+    }
+
+    pub fn volume(&self) -> T {
+        self.boxes.iter().fold(T::zero(), |v, b| v + b.volume())
+    }
+
+    pub fn volume_u128(&self) -> u128 {
+        self.boxes.iter().map(RangeD::volume_u128).sum()
+    }
+}
+
+impl<T: PrimInt, const N: usize> FromIterator<RangeD<T, N>> for BoxSet<T, N> {
+    fn from_iter<IT: IntoIterator<Item=RangeD<T, N>>>(iter: IT) -> Self {
+        let mut out = Self::new();
+        for range in iter {
+            out.insert(range);
+        }
+        out
+    }
+}
+
+pub struct RangeDIterator<T, const N: usize> {
+    ranges: RangeD<T, N>,
+    values: [T; N]
+}
+
+impl<T: PrimInt, const N: usize> RangeDIterator<T, N> {
+    pub fn new(range: &RangeD<T, N>) -> Self {
+        let mut values = range.start;
+        // an empty range in any dimension means there is nothing to iterate
+        if range.is_empty() {
+            values[0] = range.end[0];
+        }
         Self {
-            ranges: range,
-            values: range.start
+            ranges: range.clone(),
+            values
         }
     }
 }
 
-impl<'a, const N: usize> Iterator for RangeDIterator<'a, N> {
-    type Item = [usize; N];
+impl<T: PrimInt, const N: usize> Iterator for RangeDIterator<T, N> {
+    type Item = [T; N];
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.values[0] == self.ranges.end[0] {
             return None;
         }
         
-        fn increment<const N: usize>(indices: &mut [usize; N], ranges: &RangeD<N>, i: usize) {
-            indices[i] += 1;
-            if ranges.end[i] == indices[i] && i != 0 {
-                indices[i] = ranges.start[i];
-                increment(indices, ranges, i - 1);
-            }
-        }
         let item = self.values;
         
         let mut i = N - 1;
-        self.values[i] += 1;
+        self.values[i] = self.values[i] + T::one();
         while i != 0 && self.values[i] == self.ranges.end[i] {
             self.values[i] = self.ranges.start[i];
-            self.values[i - 1] += 1;
+            self.values[i - 1] = self.values[i - 1] + T::one();
             i -= 1;
         }
 
@@ -207,3 +341,62 @@ impl<'a, const N: usize> Iterator for RangeDIterator<'a, N> {
     }
 }
 
+#[test]
+fn test_box_set() {
+    // two boxes crossing like a plus sign, neither contains a corner of the other
+    let a = RangeD::from_range_1d([0..10, 4..6]);
+    let b = RangeD::from_range_1d([4..6, 0..10]);
+    assert!(a.intersects(&b) && b.intersects(&a));
+    assert!(!a.intersects(&RangeD::from_range_1d([10..12, 0..10])));
+
+    let diff = a.difference(&b);
+    assert_eq!(diff.len(), 2);
+    assert_eq!(diff.iter().map(RangeD::volume).sum::<i64>(), 16);
+
+    let cube = RangeD::<usize, 3>::from_range_1d([0..3, 0..3, 0..3]);
+    let diff = cube.difference(&RangeD::from_range_1d([1..2, 1..2, 1..2]));
+    assert_eq!(diff.len(), 6);
+    assert_eq!(diff.iter().map(RangeD::volume).sum::<usize>(), 26);
+    assert_eq!(cube.checked_offset_neg(1), None);
+    assert_eq!(cube.checked_offset_component(2, 4).map(|c| c.to_ranges()), Some([0..3, 0..3, 4..7]));
+
+    let mut set: BoxSet<i64, 2> = [a.clone(), b].into_iter().collect();
+    assert_eq!(set.volume(), 36);
+    set.insert(RangeD::from_range_1d([0..10, 0..10]));
+    assert_eq!(set.volume(), 100);
+    set.remove(&RangeD::from_range_1d([2..8, 2..8]));
+    assert_eq!(set.volume(), 64);
+    assert!(set.contains(&[1, 1]) && !set.contains(&[5, 5]));
+
+    let mut shifted = a.clone();
+    shifted.offset(-20);
+    assert_eq!(shifted.to_ranges(), [-20..-10, -16..-14]);
+    assert_eq!(shifted.iter().next(), Some([-20, -16]));
+}
+
+#[test]
+fn test_range_d_offsets_and_volume() {
+    let mut signed = RangeD::<i64, 2>::from_range_1d([-5..5, 0..2]);
+    signed.offset_component_neg(1, 10);
+    assert_eq!(signed.to_ranges(), [-5..5, -10..-8]);
+    assert_eq!(signed.volume(), 20);
+    assert_eq!(signed.checked_offset_neg(i64::MAX), None);
+
+    let wide = RangeD::<i64, 2>::from_range_1d([i64::MIN..i64::MAX, 0..2]);
+    assert_eq!(wide.volume_u128(), (u64::MAX as u128) * 2);
+    // an inverted axis counts as empty instead of wrapping around
+    assert_eq!(RangeD { start: [3i64, 0], end: [-3, 2] }.volume_u128(), 0);
+    assert_eq!(RangeD { start: [5u8], end: [5] }.volume_u128(), 0);
+
+    let mut unsigned = RangeD::<usize, 2>::from_range_1d([2..4, 0..3]);
+    assert_eq!(unsigned.checked_offset_component_neg(0, 3), None);
+    assert_eq!(unsigned.checked_offset_component_neg(0, 2).map(|r| r.to_ranges()), Some([0..2, 0..3]));
+    unsigned.offset(1);
+    assert_eq!(unsigned.to_ranges(), [3..5, 1..4]);
+}
+
+#[test]
+#[should_panic(expected = "below the minimum")]
+fn test_range_d_offset_underflow() {
+    RangeD { start: [1usize], end: [3] }.offset_neg(2);
+}
@@ -0,0 +1,360 @@
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use indexmap::IndexMap;
+use indexmap::map::Entry;
+use num::Zero;
+use crate::graph::Graph;
+
+// every search adds to these, the runner takes them after each part. searches run on
+// rayon threads aren't counted since the stats are per thread
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    pub expanded: usize,
+    pub frontier_peak: usize,
+}
+
+impl Display for SearchStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Search: {} nodes expanded, frontier peak {}", self.expanded, self.frontier_peak)
+    }
+}
+
+thread_local! {
+    static STATS: Cell<Option<SearchStats>> = const { Cell::new(None) };
+}
+
+fn record(stats: SearchStats) {
+    STATS.with(|s| {
+        let total = s.get().unwrap_or_default();
+        s.set(Some(SearchStats {
+            expanded: total.expanded + stats.expanded,
+            frontier_peak: total.frontier_peak.max(stats.frontier_peak),
+        }));
+    });
+}
+
+// None if nothing was searched since the last call
+pub fn take_stats() -> Option<SearchStats> {
+    STATS.with(|s| s.take())
+}
+
+// nodes are interned in an IndexMap next to the index of their parent and best cost so far
+fn reverse_path<N: Clone, C>(seen: &IndexMap<N, (usize, C)>, mut index: usize) -> Vec<N> {
+    let mut path = vec![];
+    while index != usize::MAX {
+        let (node, (parent, _)) = seen.get_index(index).unwrap();
+        path.push(node.clone());
+        index = *parent;
+    }
+    path.reverse();
+    path
+}
+
+// ordered so the max heap pops the lowest estimate first, ties go to the deeper node
+#[derive(Eq, PartialEq)]
+struct Frontier<C> {
+    estimate: C,
+    cost: C,
+    index: usize,
+}
+
+impl<C: Ord> Ord for Frontier<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.cmp(&self.estimate).then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+impl<C: Ord> PartialOrd for Frontier<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// the heuristic has to never overestimate for the path to be the cheapest one
+pub fn astar<N, C, FN, IN, FH, FS>(start: &N, mut successors: FN, mut heuristic: FH, mut success: FS) -> Option<(Vec<N>, C)>
+    where
+        N: Eq + Hash + Clone,
+        C: Zero + Ord + Copy,
+        FN: FnMut(&N) -> IN,
+        IN: IntoIterator<Item = (N, C)>,
+        FH: FnMut(&N) -> C,
+        FS: FnMut(&N) -> bool,
+{
+    let mut stats = SearchStats::default();
+    let mut seen: IndexMap<N, (usize, C)> = IndexMap::new();
+    seen.insert(start.clone(), (usize::MAX, C::zero()));
+    let mut heap = BinaryHeap::from([Frontier { estimate: heuristic(start), cost: C::zero(), index: 0 }]);
+
+    let result = loop {
+        stats.frontier_peak = stats.frontier_peak.max(heap.len());
+        let Some(Frontier { cost, index, .. }) = heap.pop() else { break None; };
+        let (node, &(_, best)) = seen.get_index(index).unwrap();
+        // a cheaper way here was found after this was pushed
+        if cost > best { continue; }
+        stats.expanded += 1;
+        if success(node) {
+            break Some((reverse_path(&seen, index), cost));
+        }
+
+        for (next, step) in successors(&node.clone()) {
+            let new_cost = cost + step;
+            let next_index = match seen.entry(next) {
+                Entry::Vacant(e) => {
+                    let i = e.index();
+                    e.insert((index, new_cost));
+                    i
+                }
+                Entry::Occupied(mut e) => {
+                    if e.get().1 <= new_cost { continue; }
+                    e.insert((index, new_cost));
+                    e.index()
+                }
+            };
+            let estimate = new_cost + heuristic(seen.get_index(next_index).unwrap().0);
+            heap.push(Frontier { estimate, cost: new_cost, index: next_index });
+        }
+    };
+
+    record(stats);
+    result
+}
+
+// dijkstra for small non-negative integer costs, with one bucket per total cost
+// instead of a heap
+pub fn bucket_dijkstra<N, FN, IN, FS>(start: &N, mut successors: FN, mut success: FS) -> Option<(Vec<N>, usize)>
+    where
+        N: Eq + Hash + Clone,
+        FN: FnMut(&N) -> IN,
+        IN: IntoIterator<Item = (N, usize)>,
+        FS: FnMut(&N) -> bool,
+{
+    let mut stats = SearchStats::default();
+    let mut seen: IndexMap<N, (usize, usize)> = IndexMap::new();
+    seen.insert(start.clone(), (usize::MAX, 0));
+    let mut buckets: Vec<Vec<usize>> = vec![vec![0]];
+    let mut queued = 1;
+
+    let mut cost = 0;
+    let result = loop {
+        stats.frontier_peak = stats.frontier_peak.max(queued);
+        while buckets.get(cost).is_some_and(Vec::is_empty) {
+            cost += 1;
+        }
+        let Some(index) = buckets.get_mut(cost).and_then(Vec::pop) else { break None; };
+        queued -= 1;
+        let (node, &(_, best)) = seen.get_index(index).unwrap();
+        if cost > best { continue; }
+        stats.expanded += 1;
+        if success(node) {
+            break Some((reverse_path(&seen, index), cost));
+        }
+
+        for (next, step) in successors(&node.clone()) {
+            let new_cost = cost + step;
+            let next_index = match seen.entry(next) {
+                Entry::Vacant(e) => {
+                    let i = e.index();
+                    e.insert((index, new_cost));
+                    i
+                }
+                Entry::Occupied(mut e) => {
+                    if e.get().1 <= new_cost { continue; }
+                    e.insert((index, new_cost));
+                    e.index()
+                }
+            };
+            if buckets.len() <= new_cost {
+                buckets.resize(new_cost + 1, vec![]);
+            }
+            buckets[new_cost].push(next_index);
+            queued += 1;
+        }
+    };
+
+    record(stats);
+    result
+}
+
+enum Deepening<C> {
+    Found(C),
+    // smallest estimate that went over the bound, None if nothing did
+    Exceeded(Option<C>),
+}
+
+// iterative deepening a*, only keeps the current path in memory
+pub fn ida_star<N, C, FN, IN, FH, FS>(start: &N, mut successors: FN, mut heuristic: FH, mut success: FS) -> Option<(Vec<N>, C)>
+    where
+        N: Eq + Clone,
+        C: Zero + Ord + Copy,
+        FN: FnMut(&N) -> IN,
+        IN: IntoIterator<Item = (N, C)>,
+        FH: FnMut(&N) -> C,
+        FS: FnMut(&N) -> bool,
+{
+    #[allow(clippy::too_many_arguments)]
+    fn search<N, C, FN, IN, FH, FS>(
+        path: &mut Vec<N>, cost: C, bound: C, stats: &mut SearchStats,
+        successors: &mut FN, heuristic: &mut FH, success: &mut FS,
+    ) -> Deepening<C>
+        where
+            N: Eq + Clone,
+            C: Zero + Ord + Copy,
+            FN: FnMut(&N) -> IN,
+            IN: IntoIterator<Item = (N, C)>,
+            FH: FnMut(&N) -> C,
+            FS: FnMut(&N) -> bool,
+    {
+        let node = path.last().unwrap().clone();
+        let estimate = cost + heuristic(&node);
+        if estimate > bound { return Deepening::Exceeded(Some(estimate)); }
+        stats.expanded += 1;
+        stats.frontier_peak = stats.frontier_peak.max(path.len());
+        if success(&node) { return Deepening::Found(cost); }
+
+        let mut next_bound: Option<C> = None;
+        for (next, step) in successors(&node) {
+            if path.contains(&next) { continue; }
+            path.push(next);
+            match search(path, cost + step, bound, stats, successors, heuristic, success) {
+                Deepening::Found(c) => return Deepening::Found(c),
+                Deepening::Exceeded(Some(b)) => next_bound = Some(next_bound.map_or(b, |n| n.min(b))),
+                Deepening::Exceeded(None) => {}
+            }
+            path.pop();
+        }
+        Deepening::Exceeded(next_bound)
+    }
+
+    let mut stats = SearchStats::default();
+    let mut path = vec![start.clone()];
+    let mut bound = heuristic(start);
+    let result = loop {
+        match search(&mut path, C::zero(), bound, &mut stats, &mut successors, &mut heuristic, &mut success) {
+            Deepening::Found(cost) => break Some((path, cost)),
+            Deepening::Exceeded(Some(next)) => bound = next,
+            Deepening::Exceeded(None) => break None,
+        }
+    };
+
+    record(stats);
+    result
+}
+
+// grows a bfs layer from whichever side has the smaller frontier until they meet.
+// `predecessors` walks edges backwards, for undirected searches it's just `successors`
+pub fn bidirectional_bfs<N, FN, IN, FP, IP>(start: &N, goal: &N, mut successors: FN, mut predecessors: FP) -> Option<Vec<N>>
+    where
+        N: Eq + Hash + Clone,
+        FN: FnMut(&N) -> IN,
+        IN: IntoIterator<Item = N>,
+        FP: FnMut(&N) -> IP,
+        IP: IntoIterator<Item = N>,
+{
+    let mut stats = SearchStats::default();
+    // node -> the node it was reached from, on the respective side
+    let mut forward: HashMap<N, Option<N>> = HashMap::from([(start.clone(), None)]);
+    let mut backward: HashMap<N, Option<N>> = HashMap::from([(goal.clone(), None)]);
+    let mut forward_layer = VecDeque::from([start.clone()]);
+    let mut backward_layer = VecDeque::from([goal.clone()]);
+
+    let meeting = if start == goal { Some(start.clone()) } else { None };
+    let meeting = meeting.or_else(|| loop {
+        stats.frontier_peak = stats.frontier_peak.max(forward_layer.len() + backward_layer.len());
+        if forward_layer.is_empty() || backward_layer.is_empty() { break None; }
+
+        let grow_forward = forward_layer.len() <= backward_layer.len();
+        let (layer, parents, other) = if grow_forward {
+            (&mut forward_layer, &mut forward, &backward)
+        } else {
+            (&mut backward_layer, &mut backward, &forward)
+        };
+        let mut next_layer = VecDeque::new();
+        let mut found = None;
+        'layer: for node in layer.drain(..) {
+            stats.expanded += 1;
+            let neighbours = if grow_forward {
+                successors(&node).into_iter().collect::<Vec<_>>()
+            } else {
+                predecessors(&node).into_iter().collect::<Vec<_>>()
+            };
+            for next in neighbours {
+                if parents.contains_key(&next) { continue; }
+                parents.insert(next.clone(), Some(node.clone()));
+                if other.contains_key(&next) {
+                    found = Some(next);
+                    break 'layer;
+                }
+                next_layer.push_back(next);
+            }
+        }
+        if found.is_some() { break found; }
+        *layer = next_layer;
+    });
+
+    record(stats);
+    let meeting = meeting?;
+    let mut path = vec![];
+    let mut cur = Some(meeting.clone());
+    while let Some(node) = cur {
+        cur = forward[&node].clone();
+        path.push(node);
+    }
+    path.reverse();
+    let mut cur = backward[&meeting].clone();
+    while let Some(node) = cur {
+        cur = backward[&node].clone();
+        path.push(node);
+    }
+    Some(path)
+}
+
+impl<N, E> Graph<N, E>
+    where
+        N: Eq + Hash + Clone,
+{
+    // fewest edges from `from` to `to`, as node ids
+    pub fn bfs_path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let preds = self.predecessors();
+        bidirectional_bfs(
+            &from,
+            &to,
+            |&n| self.neighbours(n).iter().map(|(to, _)| *to).collect::<Vec<_>>(),
+            |&n| preds[n].clone(),
+        )
+    }
+}
+
+#[test]
+fn test_searches() {
+    // 5x5 open grid with a wall down the middle that has a gap at the bottom
+    let open = |(x, y): (i32, i32)| (0..5).contains(&x) && (0..5).contains(&y) && !(x == 2 && y < 4);
+    let successors = |&(x, y): &(i32, i32)| {
+        [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].into_iter()
+            .filter(|&p| open(p))
+            .map(|p| (p, 1))
+            .collect::<Vec<_>>()
+    };
+    let goal = (4, 0);
+    let manhattan = |&(x, y): &(i32, i32)| ((x - goal.0).abs() + (y - goal.1).abs()) as usize;
+
+    take_stats();
+    let (path, cost) = astar(&(0, 0), successors, manhattan, |p| *p == goal).unwrap();
+    assert_eq!(cost, 12);
+    assert_eq!(path.len(), 13);
+    let stats = take_stats().unwrap();
+    assert!(stats.expanded > 0 && stats.frontier_peak > 0);
+    assert_eq!(take_stats(), None);
+
+    assert_eq!(bucket_dijkstra(&(0, 0), successors, |p| *p == goal).unwrap().1, 12);
+    assert_eq!(ida_star(&(0, 0), successors, manhattan, |p| *p == goal).unwrap().1, 12);
+    let bfs = bidirectional_bfs(&(0, 0), &goal, |p| successors(p).into_iter().map(|(p, _)| p), |p| successors(p).into_iter().map(|(p, _)| p)).unwrap();
+    assert_eq!((bfs.first(), bfs.last(), bfs.len()), (Some(&(0, 0)), Some(&goal), 13));
+    assert_eq!(astar(&(0, 0), successors, manhattan, |p| *p == (2, 0)), None);
+
+    let graph = Graph::from_successors([(0, 0)], successors, false);
+    let path = graph.bfs_path(graph.id(&(0, 0)).unwrap(), graph.id(&goal).unwrap()).unwrap();
+    assert_eq!(path.len(), 13);
+}
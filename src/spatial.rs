@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use num::{Integer, PrimInt};
+use crate::ranges::RangeD;
+
+// boxes bucketed into a uniform grid of `cell`-sized cubes. every box is listed in each
+// bucket it touches, so a query only has to look at the buckets its own box touches
+pub struct BoxIndex<T, const N: usize> {
+    cell: T,
+    boxes: Vec<RangeD<T, N>>,
+    buckets: HashMap<[T; N], Vec<usize>>,
+    // lowest bucket on each axis, so downward scans know where to stop. None until a
+    // non-empty box goes in
+    min_bucket: Option<[T; N]>,
+}
+
+impl<T, const N: usize> BoxIndex<T, N>
+    where
+        T: PrimInt + Integer + Hash,
+{
+    pub fn new(cell: T) -> Self {
+        assert!(cell > T::zero(), "bucket size must be positive");
+        Self {
+            cell,
+            boxes: vec![],
+            buckets: HashMap::new(),
+            min_bucket: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.boxes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.boxes.is_empty()
+    }
+
+    pub fn get(&self, id: usize) -> &RangeD<T, N> {
+        &self.boxes[id]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &RangeD<T, N>)> {
+        self.boxes.iter().enumerate()
+    }
+
+    // ids are handed out in insertion order. empty boxes are kept but never found
+    pub fn insert(&mut self, range: RangeD<T, N>) -> usize {
+        let id = self.boxes.len();
+        if !range.is_empty() {
+            let keys = self.bucket_range(&range);
+            let min_bucket = self.min_bucket.get_or_insert(keys.start);
+            for i in 0..N {
+                min_bucket[i] = min_bucket[i].min(keys.start[i]);
+            }
+            for key in &keys {
+                self.buckets.entry(key).or_default().push(id);
+            }
+        }
+        self.boxes.push(range);
+        id
+    }
+
+    fn bucket_range(&self, range: &RangeD<T, N>) -> RangeD<T, N> {
+        RangeD {
+            start: range.start.map(|x| x.div_floor(&self.cell)),
+            end: range.end.map(|x| (x - T::one()).div_floor(&self.cell) + T::one()),
+        }
+    }
+
+    fn candidates(&self, range: &RangeD<T, N>) -> Vec<usize> {
+        if range.is_empty() { return vec![]; }
+        let mut ids = self.bucket_range(range).iter()
+            .filter_map(|key| self.buckets.get(&key))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    // sorted ids of every box sharing at least one cell with `range`
+    pub fn overlapping(&self, range: &RangeD<T, N>) -> Vec<usize> {
+        self.candidates(range).into_iter()
+            .filter(|&id| self.boxes[id].intersects(range))
+            .collect()
+    }
+
+    // boxes resting right under `range` along `axis`: they end where it starts and
+    // share some cells with it on every other axis
+    pub fn directly_below(&self, range: &RangeD<T, N>, axis: usize) -> Vec<usize> {
+        let Some(bottom) = range.start[axis].checked_sub(&T::one()) else { return vec![]; };
+        let mut layer = range.clone();
+        layer.start[axis] = bottom;
+        layer.end[axis] = range.start[axis];
+        self.overlapping(&layer)
+    }
+
+    pub fn directly_above(&self, range: &RangeD<T, N>, axis: usize) -> Vec<usize> {
+        let Some(top) = range.end[axis].checked_add(&T::one()) else { return vec![]; };
+        let mut layer = range.clone();
+        layer.start[axis] = range.end[axis];
+        layer.end[axis] = top;
+        self.overlapping(&layer)
+    }
+
+    // top of the highest box under `range` along `axis` whose footprint overlaps it
+    pub fn highest_below(&self, range: &RangeD<T, N>, axis: usize) -> Option<T> {
+        let min_bucket = self.min_bucket?;
+        if range.is_empty() { return None; }
+        let mut column = range.clone();
+        column.start[axis] = min_bucket[axis] * self.cell;
+        column.end[axis] = range.start[axis];
+        self.overlapping(&column).into_iter()
+            .map(|id| self.boxes[id].end[axis])
+            .max()
+    }
+}
+
+#[test]
+fn test_box_index() {
+    let mut index = BoxIndex::new(4);
+    // empty boxes are stored but don't count as something to land on
+    let empty = index.insert(RangeD::from_range_1d([0..0, 0..1, 0..1]));
+    assert_eq!(index.highest_below(&RangeD::from_range_1d([0..1, 0..1, 5..6]), 2), None);
+    assert_eq!(index.get(empty).volume(), 0);
+    let floor = index.insert(RangeD::from_range_1d([-10..10, -10..10, -1..0]));
+    let a = index.insert(RangeD::from_range_1d([0..3, 0..1, 0..2]));
+    let b = index.insert(RangeD::from_range_1d([2..3, 0..5, 2..3]));
+    let c = index.insert(RangeD::from_range_1d([-5..-3, 0..5, 6..9]));
+
+    assert_eq!(index.overlapping(&RangeD::from_range_1d([0..10, 0..10, 0..10])), vec![a, b]);
+    assert_eq!(index.directly_below(index.get(b), 2), vec![a]);
+    assert_eq!(index.directly_below(index.get(a), 2), vec![floor]);
+    assert_eq!(index.directly_above(index.get(a), 2), vec![b]);
+    assert_eq!(index.directly_below(index.get(c), 2), vec![]);
+    assert_eq!(index.highest_below(index.get(c), 2), Some(0));
+    assert_eq!(index.highest_below(&RangeD::from_range_1d([2..3, 3..4, 20..21]), 2), Some(3));
+}
@@ -1,24 +1,373 @@
-﻿use std::ops::Add;
+﻿use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+use num::{Signed, Zero};
 
-#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
-pub struct V2I {
-    pub x: i32,
-    pub y: i32
+pub type V2I = Vector<i32, 2>;
+
+#[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Vector<T, const N: usize>(pub [T; N]);
+
+impl<T, const N: usize> Vector<T, N> {
+    pub fn new(coords: [T; N]) -> Self {
+        Self(coords)
+    }
+
+    pub fn map<X, FN: FnMut(T) -> X>(self, func: FN) -> Vector<X, N> {
+        Vector(self.0.map(func))
+    }
 }
 
-impl V2I {
-    pub fn new(x: i32, y: i32) -> V2I {
-        V2I {x, y}
+impl<T: Copy, const N: usize> Vector<T, N> {
+    pub fn zip_with<FN: FnMut(T, T) -> T>(self, other: Self, mut func: FN) -> Self {
+        let mut out = self.0;
+        out.iter_mut().zip(other.0).for_each(|(a, b)| *a = func(*a, b));
+        Self(out)
+    }
+
+    pub fn min(self, other: Self) -> Self where T: Ord {
+        self.zip_with(other, |a, b| a.min(b))
+    }
+
+    pub fn max(self, other: Self) -> Self where T: Ord {
+        self.zip_with(other, |a, b| a.max(b))
     }
 }
 
-impl Add for V2I {
-    type Output = V2I;
+impl<T: Copy + Zero, const N: usize> Vector<T, N> {
+    pub fn zero() -> Self {
+        Self([T::zero(); N])
+    }
+
+    pub fn dot(self, other: Self) -> T where T: Mul<Output = T> {
+        self.0.into_iter().zip(other.0).fold(T::zero(), |acc, (a, b)| acc + a * b)
+    }
+}
+
+impl<T: Copy + Signed + Ord, const N: usize> Vector<T, N> {
+    pub fn manhattan(self) -> T {
+        self.0.into_iter().fold(T::zero(), |acc, x| acc + x.abs())
+    }
+
+    pub fn chebyshev(self) -> T {
+        self.0.into_iter().fold(T::zero(), |acc, x| acc.max(x.abs()))
+    }
+}
+
+impl<T: Copy> Vector<T, 2> {
+    pub fn x(&self) -> T { self.0[0] }
+    pub fn y(&self) -> T { self.0[1] }
+}
+
+impl<T: Copy> Vector<T, 3> {
+    pub fn x(&self) -> T { self.0[0] }
+    pub fn y(&self) -> T { self.0[1] }
+    pub fn z(&self) -> T { self.0[2] }
+}
+
+impl<T, const N: usize> Index<usize> for Vector<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for Vector<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl<T: Copy + Add<Output = T>, const N: usize> Add for Vector<T, N> {
+    type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        Self::Output {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y
+        self.zip_with(rhs, |a, b| a + b)
+    }
+}
+
+impl<T: Copy + Sub<Output = T>, const N: usize> Sub for Vector<T, N> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a - b)
+    }
+}
+
+impl<T: Copy + Add<Output = T>, const N: usize> AddAssign for Vector<T, N> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: Copy + Sub<Output = T>, const N: usize> SubAssign for Vector<T, N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: Neg<Output = T>, const N: usize> Neg for Vector<T, N> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self.map(|x| -x)
+    }
+}
+
+impl<T: Copy + Mul<Output = T>, const N: usize> Mul<T> for Vector<T, N> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        self.map(|x| x * rhs)
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Vector<T, N> {
+    fn from(value: [T; N]) -> Self {
+        Self(value)
+    }
+}
+
+impl<T, const N: usize> From<Vector<T, N>> for [T; N] {
+    fn from(value: Vector<T, N>) -> Self {
+        value.0
+    }
+}
+
+impl<T> From<(T, T)> for Vector<T, 2> {
+    fn from((x, y): (T, T)) -> Self {
+        Self([x, y])
+    }
+}
+
+impl<T> From<Vector<T, 2>> for (T, T) {
+    fn from(value: Vector<T, 2>) -> Self {
+        let [x, y] = value.0;
+        (x, y)
+    }
+}
+
+impl<T> From<(T, T, T)> for Vector<T, 3> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Self([x, y, z])
+    }
+}
+
+impl<T> From<Vector<T, 3>> for (T, T, T) {
+    fn from(value: Vector<T, 3>) -> Self {
+        let [x, y, z] = value.0;
+        (x, y, z)
+    }
+}
+
+#[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Dir4 {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Dir4 {
+    // clockwise, starting from north
+    pub const ALL: [Dir4; 4] = [Dir4::North, Dir4::East, Dir4::South, Dir4::West];
+
+    pub fn rotate_cw(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn rotate_ccw(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+
+    // bounce off a `/` mirror
+    pub fn reflect_slash(self) -> Self {
+        match self {
+            Dir4::North => Dir4::East,
+            Dir4::East => Dir4::North,
+            Dir4::South => Dir4::West,
+            Dir4::West => Dir4::South,
+        }
+    }
+
+    // bounce off a `\` mirror
+    pub fn reflect_backslash(self) -> Self {
+        match self {
+            Dir4::North => Dir4::West,
+            Dir4::West => Dir4::North,
+            Dir4::South => Dir4::East,
+            Dir4::East => Dir4::South,
+        }
+    }
+
+    pub fn is_horizontal(self) -> bool {
+        matches!(self, Dir4::East | Dir4::West)
+    }
+
+    // y points down, same as the grid
+    pub fn unit<T: Signed + Copy>(self) -> Vector<T, 2> {
+        match self {
+            Dir4::North => Vector([T::zero(), -T::one()]),
+            Dir4::East => Vector([T::one(), T::zero()]),
+            Dir4::South => Vector([T::zero(), T::one()]),
+            Dir4::West => Vector([-T::one(), T::zero()]),
+        }
+    }
+}
+
+impl TryFrom<char> for Dir4 {
+    type Error = ();
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Ok(match c {
+            'U' | 'N' | '^' => Dir4::North,
+            'R' | 'E' | '>' => Dir4::East,
+            'D' | 'S' | 'v' => Dir4::South,
+            'L' | 'W' | '<' => Dir4::West,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl FromStr for Dir4 {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => c.try_into(),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Dir8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Dir8 {
+    // clockwise, starting from north
+    pub const ALL: [Dir8; 8] = [Dir8::North, Dir8::NorthEast, Dir8::East, Dir8::SouthEast,
+        Dir8::South, Dir8::SouthWest, Dir8::West, Dir8::NorthWest];
+
+    // 45 degrees
+    pub fn rotate_cw(self) -> Self {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    pub fn rotate_ccw(self) -> Self {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    pub fn rotate_cw_90(self) -> Self {
+        Self::ALL[(self as usize + 2) % 8]
+    }
+
+    pub fn rotate_ccw_90(self) -> Self {
+        Self::ALL[(self as usize + 6) % 8]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self as usize % 2 == 1
+    }
+
+    // y points down, same as the grid
+    pub fn unit<T: Signed + Copy>(self) -> Vector<T, 2> {
+        match self {
+            Dir8::North => Dir4::North.unit(),
+            Dir8::East => Dir4::East.unit(),
+            Dir8::South => Dir4::South.unit(),
+            Dir8::West => Dir4::West.unit(),
+            diagonal => diagonal.rotate_ccw().unit() + diagonal.rotate_cw().unit(),
         }
     }
-}
\ No newline at end of file
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(value: Dir4) -> Self {
+        Dir8::ALL[value as usize * 2]
+    }
+}
+
+impl FromStr for Dir8 {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "NE" | "UR" => Dir8::NorthEast,
+            "SE" | "DR" => Dir8::SouthEast,
+            "SW" | "DL" => Dir8::SouthWest,
+            "NW" | "UL" => Dir8::NorthWest,
+            s => s.parse::<Dir4>()?.into(),
+        })
+    }
+}
+
+#[test]
+fn test_vector_ops() {
+    let a = Vector::new([1i64, -2, 3]);
+    let b: Vector<i64, 3> = (4, 5, -6).into();
+    assert_eq!(a + b, Vector([5, 3, -3]));
+    assert_eq!(a - b, Vector([-3, -7, 9]));
+    assert_eq!(-a * 2, Vector([-2, 4, -6]));
+    assert_eq!(a.dot(b), 4 - 10 - 18);
+    assert_eq!((a - b).manhattan(), 19);
+    assert_eq!((a - b).chebyshev(), 9);
+    assert_eq!(a.min(b), Vector([1, -2, -6]));
+    assert_eq!(<(i64, i64, i64)>::from(a.max(b)), (4, 5, 3));
+}
+
+#[test]
+fn test_directions() {
+    for d in Dir4::ALL {
+        assert_eq!(d.rotate_cw().rotate_ccw(), d);
+        assert_eq!(d.rotate_cw().rotate_cw(), d.opposite());
+        assert_eq!(d.unit::<i64>() + d.opposite().unit(), Vector::zero());
+        // mirrors are their own inverse once the beam turns around
+        assert_eq!(d.reflect_slash().opposite().reflect_slash(), d.opposite());
+        assert_eq!(d.reflect_backslash().opposite().reflect_backslash(), d.opposite());
+    }
+    for d in Dir8::ALL {
+        assert_eq!(d.rotate_cw().rotate_ccw(), d);
+        assert_eq!(d.rotate_cw_90().rotate_ccw_90(), d);
+        assert_eq!(d.rotate_cw_90().rotate_cw_90(), d.opposite());
+    }
+    assert_eq!(Dir8::NorthEast.unit::<i64>(), Vector([1, -1]));
+
+    // heading east, `/` sends the beam up and `\` sends it down
+    assert_eq!(Dir4::East.reflect_slash(), Dir4::North);
+    assert_eq!(Dir4::East.reflect_backslash(), Dir4::South);
+    assert_eq!(Dir4::North.reflect_slash(), Dir4::East);
+    assert_eq!(Dir4::North.reflect_backslash(), Dir4::West);
+
+    for alphabet in ["URDL", "NESW", "^>v<"] {
+        let parsed = alphabet.chars().map(Dir4::try_from).collect::<Result<Vec<_>, _>>();
+        assert_eq!(parsed, Ok(Dir4::ALL.to_vec()));
+    }
+    for bad in ['u', 'n', 'V', 'x', ' '] {
+        assert_eq!(Dir4::try_from(bad), Err(()));
+    }
+    assert_eq!("R".parse::<Dir4>(), Ok(Dir4::East));
+    assert_eq!("".parse::<Dir4>(), Err(()));
+    assert_eq!("RR".parse::<Dir4>(), Err(()));
+    assert_eq!("UL".parse::<Dir8>(), Ok(Dir8::NorthWest));
+    assert_eq!("SE".parse::<Dir8>(), Ok(Dir8::SouthEast));
+    assert_eq!("W".parse::<Dir8>(), Ok(Dir8::West));
+    assert_eq!("NN".parse::<Dir8>(), Err(()));
+}